@@ -2,7 +2,10 @@
 fn main() -> windows_service::Result<()> {
     use std::ffi::OsString;
     use windows_service::{
-        service::{ServiceAccess, ServiceErrorControl, ServiceInfo, ServiceStartType, ServiceType},
+        service::{
+            ServiceAccess, ServiceBinary, ServiceErrorControl, ServiceInfo, ServiceStartType,
+            ServiceType,
+        },
         service_manager::{ServiceManager, ServiceManagerAccess},
     };
 
@@ -22,8 +25,11 @@ fn main() -> windows_service::Result<()> {
         service_type: ServiceType::OWN_PROCESS,
         start_type: ServiceStartType::OnDemand,
         error_control: ServiceErrorControl::Normal,
-        executable_path: service_binary_path,
-        launch_arguments: vec![],
+        binary: ServiceBinary::Executable {
+            path: service_binary_path,
+            args: vec![],
+        },
+        load_order_group: None,
         dependencies: vec![],
         account_name: None, // run as System
         account_password: None,
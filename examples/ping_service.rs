@@ -33,8 +33,8 @@ mod ping_service {
     use windows_service::{
         define_windows_service,
         service::{
-            ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
-            ServiceType,
+            ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceFlags, ServiceState,
+            ServiceStatus, ServiceType,
         },
         service_control_handler::{self, ServiceControlHandlerResult},
         service_dispatcher, Result,
@@ -110,6 +110,7 @@ mod ping_service {
             checkpoint: 0,
             wait_hint: Duration::default(),
             process_id: None,
+            service_flags: ServiceFlags::empty(),
         })?;
 
         // For demo purposes this service sends a UDP packet once a second.
@@ -141,6 +142,7 @@ mod ping_service {
             checkpoint: 0,
             wait_hint: Duration::default(),
             process_id: None,
+            service_flags: ServiceFlags::empty(),
         })?;
 
         Ok(())
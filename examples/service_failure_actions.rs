@@ -4,7 +4,7 @@ fn main() -> windows_service::Result<()> {
     use std::time::Duration;
     use windows_service::{
         service::{
-            ServiceAccess, ServiceAction, ServiceActionType, ServiceErrorControl,
+            ServiceAccess, ServiceAction, ServiceActionType, ServiceBinary, ServiceErrorControl,
             ServiceFailureActions, ServiceFailureResetPeriod, ServiceInfo, ServiceStartType,
             ServiceType,
         },
@@ -26,8 +26,11 @@ fn main() -> windows_service::Result<()> {
         service_type: ServiceType::OWN_PROCESS,
         start_type: ServiceStartType::OnDemand,
         error_control: ServiceErrorControl::Normal,
-        executable_path: service_binary_path,
-        launch_arguments: vec![],
+        binary: ServiceBinary::Executable {
+            path: service_binary_path,
+            args: vec![],
+        },
+        load_order_group: None,
         dependencies: vec![],
         account_name: None, // run as System
         account_password: None,
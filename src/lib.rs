@@ -115,8 +115,8 @@
 //! use std::ffi::OsString;
 //! use std::time::Duration;
 //! use windows_service::service::{
-//!     ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
-//!     ServiceType,
+//!     ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceFlags, ServiceState,
+//!     ServiceStatus, ServiceType,
 //! };
 //! use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
 //!
@@ -154,6 +154,8 @@
 //!         wait_hint: Duration::default(),
 //!         // Unused for setting status
 //!         process_id: None,
+//!         // Unused for setting status
+//!         service_flags: ServiceFlags::empty(),
 //!     };
 //!
 //!     // Tell the system that the service is running now
@@ -176,6 +178,14 @@
 
 #![cfg(windows)]
 
+use windows_sys::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_FAILED_SERVICE_CONTROLLER_CONNECT, ERROR_INVALID_SERVICE_CONTROL,
+    ERROR_SERVICE_ALREADY_RUNNING, ERROR_SERVICE_CANNOT_ACCEPT_CTRL, ERROR_SERVICE_DATABASE_LOCKED,
+    ERROR_SERVICE_DEPENDENCY_FAIL, ERROR_SERVICE_DISABLED, ERROR_SERVICE_DOES_NOT_EXIST,
+    ERROR_SERVICE_EXISTS, ERROR_SERVICE_MARKED_FOR_DELETE, ERROR_SERVICE_NOT_ACTIVE,
+    ERROR_SERVICE_REQUEST_TIMEOUT,
+};
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -189,6 +199,29 @@ pub enum Error {
     ArgumentHasNulByte(&'static str),
     /// An argument array contains a nul byte in element at the given index
     ArgumentArrayElementHasNulByte(&'static str, usize),
+    /// An instance of the service is already running
+    ServiceAlreadyRunning,
+    /// Timed out waiting for the service to reach the desired state
+    Timeout,
+    /// The wait for the service to reach the desired state was cancelled
+    Cancelled,
+    /// Timed out waiting for the service to stop while restarting it
+    StopTimeout,
+    /// Timed out waiting for the service to start while restarting it
+    StartTimeout,
+    /// The service stopped before reaching the running state, with the given exit code
+    ServiceFailedToStart(service::ServiceExitCode),
+    /// The requested control code is not valid or not accepted by the service
+    InvalidServiceControl,
+    /// The service information contains an inconsistent combination of values
+    InvalidServiceInfo(&'static str),
+    /// The service name is empty or contains a slash or a backslash, or no services are given
+    InvalidServiceName,
+    /// The service control dispatcher cannot connect to the system, because the program was not
+    /// launched by the system as a service
+    NotStartedByServiceController,
+    /// The services depend on each other in a cycle, which includes the given service
+    DependencyCycle(std::ffi::OsString),
     /// IO error in winapi call
     Winapi(std::io::Error),
 }
@@ -216,17 +249,402 @@ impl std::fmt::Display for Error {
                 "{} contains a nul byte in element at {} index",
                 name, index
             ),
+            Self::ServiceAlreadyRunning => {
+                write!(f, "an instance of the service is already running")
+            }
+            Self::Timeout => write!(f, "timed out waiting for the service"),
+            Self::Cancelled => write!(f, "cancelled waiting for the service"),
+            Self::StopTimeout => write!(f, "timed out waiting for the service to stop"),
+            Self::StartTimeout => write!(f, "timed out waiting for the service to start"),
+            Self::ServiceFailedToStart(exit_code) => {
+                write!(f, "the service failed to start: {:?}", exit_code)
+            }
+            Self::InvalidServiceControl => {
+                write!(f, "the requested control is not valid for the service")
+            }
+            Self::InvalidServiceInfo(reason) => write!(f, "invalid service info: {}", reason),
+            Self::InvalidServiceName => write!(f, "invalid service name"),
+            Self::NotStartedByServiceController => {
+                write!(
+                    f,
+                    "the program was not started by the service control manager"
+                )
+            }
+            Self::DependencyCycle(name) => {
+                write!(f, "dependency cycle includes the service {:?}", name)
+            }
             Self::Winapi(_) => write!(f, "IO error in winapi call"),
         }
     }
 }
 
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        use std::io::ErrorKind;
+
+        match error {
+            Error::Winapi(e) => e,
+            Error::ServiceAlreadyRunning
+            | Error::InvalidServiceControl
+            | Error::NotStartedByServiceController => {
+                let code = error.raw_os_error().expect("error must have a raw code");
+                std::io::Error::from_raw_os_error(code)
+            }
+            Error::Timeout | Error::StopTimeout | Error::StartTimeout => {
+                std::io::Error::new(ErrorKind::TimedOut, error)
+            }
+            Error::Cancelled => std::io::Error::new(ErrorKind::Interrupted, error),
+            Error::ServiceFailedToStart(_) => std::io::Error::new(ErrorKind::Other, error),
+            Error::ParseValue(..) | Error::DependencyCycle(_) => {
+                std::io::Error::new(ErrorKind::InvalidData, error)
+            }
+            Error::LaunchArgumentsNotSupported
+            | Error::InvalidServiceInfo(_)
+            | Error::InvalidServiceName
+            | Error::ArgumentHasNulByte(_)
+            | Error::ArgumentArrayElementHasNulByte(..) => {
+                std::io::Error::new(ErrorKind::InvalidInput, error)
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Returns the raw Win32 error code reported by the system, if any.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Self::ServiceAlreadyRunning => Some(ERROR_SERVICE_ALREADY_RUNNING as i32),
+            Self::InvalidServiceControl => Some(ERROR_INVALID_SERVICE_CONTROL as i32),
+            Self::NotStartedByServiceController => {
+                Some(ERROR_FAILED_SERVICE_CONTROLLER_CONNECT as i32)
+            }
+            Self::Winapi(e) => e.raw_os_error(),
+            _ => None,
+        }
+    }
+
+    /// Classifies the error reported by the system into one of the common service errors.
+    ///
+    /// Returns `None` for errors that are not reported by the system or not classified.
+    /// Use [`Error::raw_os_error`] to obtain the error code in that case.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    /// use windows_service::ServiceError;
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// match manager.open_service("my_service", ServiceAccess::QUERY_STATUS) {
+    ///     Ok(_service) => println!("The service is installed"),
+    ///     Err(e) if e.as_service_error() == Some(ServiceError::DoesNotExist) => {
+    ///         println!("The service is not installed")
+    ///     }
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_service_error(&self) -> Option<ServiceError> {
+        self.raw_os_error()
+            .and_then(|code| u32::try_from(code).ok())
+            .and_then(ServiceError::from_raw)
+    }
+
+    /// Returns `true` if the system denied the access, typically because the process does not run
+    /// with administrator rights. See [`is_elevated`].
+    pub fn is_access_denied(&self) -> bool {
+        self.as_service_error() == Some(ServiceError::AccessDenied)
+    }
+}
+
+/// Returns `true` if the current process runs with administrator rights.
+///
+/// Most of the operations changing the services database, such as creating, deleting or starting
+/// services, require administrator rights, which the processes started by the administrators
+/// only receive when elevated via the user account control. This allows checking for that
+/// upfront instead of failing with an error for which [`Error::is_access_denied`] is `true`.
+///
+/// Returns `false` if the elevation of the process cannot be determined.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// if !windows_service::is_elevated() {
+///     eprintln!("Please run as administrator");
+/// }
+/// ```
+pub fn is_elevated() -> bool {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, HANDLE},
+        Security,
+        System::Threading::{GetCurrentProcess, OpenProcessToken},
+    };
+
+    let mut token: HANDLE = 0;
+    if unsafe { OpenProcessToken(GetCurrentProcess(), Security::TOKEN_QUERY, &mut token) } == 0 {
+        return false;
+    }
+
+    let mut elevation = Security::TOKEN_ELEVATION { TokenIsElevated: 0 };
+    let mut returned_size: u32 = 0;
+    let success = unsafe {
+        Security::GetTokenInformation(
+            token,
+            Security::TokenElevation,
+            &mut elevation as *mut _ as *mut std::os::raw::c_void,
+            std::mem::size_of::<Security::TOKEN_ELEVATION>() as u32,
+            &mut returned_size,
+        )
+    };
+    unsafe { CloseHandle(token) };
+
+    success != 0 && elevation.TokenIsElevated != 0
+}
+
+/// Enum describing the common errors reported by the system when working with services.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum ServiceError {
+    /// The access to the service or the service control manager is denied.
+    AccessDenied = ERROR_ACCESS_DENIED,
+    /// The service does not exist.
+    DoesNotExist = ERROR_SERVICE_DOES_NOT_EXIST,
+    /// The service already exists.
+    AlreadyExists = ERROR_SERVICE_EXISTS,
+    /// An instance of the service is already running.
+    AlreadyRunning = ERROR_SERVICE_ALREADY_RUNNING,
+    /// The service has not been started.
+    NotActive = ERROR_SERVICE_NOT_ACTIVE,
+    /// The service is disabled.
+    Disabled = ERROR_SERVICE_DISABLED,
+    /// The service has been marked for deletion.
+    MarkedForDelete = ERROR_SERVICE_MARKED_FOR_DELETE,
+    /// The service did not respond to the start or control request in a timely fashion.
+    RequestTimeout = ERROR_SERVICE_REQUEST_TIMEOUT,
+    /// The dependency service or group failed to start.
+    DependencyFailed = ERROR_SERVICE_DEPENDENCY_FAIL,
+    /// The requested control code is not valid or not accepted by the service.
+    InvalidServiceControl = ERROR_INVALID_SERVICE_CONTROL,
+    /// The service cannot accept control messages at this time.
+    CannotAcceptControl = ERROR_SERVICE_CANNOT_ACCEPT_CTRL,
+    /// The services database is locked.
+    DatabaseLocked = ERROR_SERVICE_DATABASE_LOCKED,
+}
+
+impl ServiceError {
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        match raw {
+            ERROR_ACCESS_DENIED => Some(Self::AccessDenied),
+            ERROR_SERVICE_DOES_NOT_EXIST => Some(Self::DoesNotExist),
+            ERROR_SERVICE_EXISTS => Some(Self::AlreadyExists),
+            ERROR_SERVICE_ALREADY_RUNNING => Some(Self::AlreadyRunning),
+            ERROR_SERVICE_NOT_ACTIVE => Some(Self::NotActive),
+            ERROR_SERVICE_DISABLED => Some(Self::Disabled),
+            ERROR_SERVICE_MARKED_FOR_DELETE => Some(Self::MarkedForDelete),
+            ERROR_SERVICE_REQUEST_TIMEOUT => Some(Self::RequestTimeout),
+            ERROR_SERVICE_DEPENDENCY_FAIL => Some(Self::DependencyFailed),
+            ERROR_INVALID_SERVICE_CONTROL => Some(Self::InvalidServiceControl),
+            ERROR_SERVICE_CANNOT_ACCEPT_CTRL => Some(Self::CannotAcceptControl),
+            ERROR_SERVICE_DATABASE_LOCKED => Some(Self::DatabaseLocked),
+            _ => None,
+        }
+    }
+
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Returns `true` for the errors that are expected to go away when the operation is retried
+    /// shortly after, such as [`ServiceError::DatabaseLocked`] while another process is
+    /// installing a service.
+    ///
+    /// The transient errors are [`ServiceError::DatabaseLocked`] and
+    /// [`ServiceError::CannotAcceptControl`]. See [`RetryPolicy`].
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::DatabaseLocked | Self::CannotAcceptControl)
+    }
+}
+
+/// A policy for retrying the operations that fail with a transient error.
+///
+/// Only the errors classified as transient by [`ServiceError::is_transient`] are retried, all the
+/// other errors, such as [`ServiceError::AccessDenied`], are returned right away. The delay between
+/// the attempts starts at the given backoff and doubles after each failed attempt.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use windows_service::service::ServiceAccess;
+/// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+/// use windows_service::RetryPolicy;
+///
+/// # fn main() -> windows_service::Result<()> {
+/// let retry_policy = RetryPolicy::new(5, Duration::from_millis(100));
+/// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+/// let my_service = manager.open_service("my_service", ServiceAccess::STOP)?;
+/// retry_policy.run(|| my_service.stop())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    attempts: u32,
+    backoff: std::time::Duration,
+}
+
+impl RetryPolicy {
+    /// Create a policy making up to the given number of attempts, including the first one.
+    ///
+    /// The operation is attempted at least once, even if `attempts` is zero.
+    pub fn new(attempts: u32, backoff: std::time::Duration) -> Self {
+        RetryPolicy { attempts, backoff }
+    }
+
+    /// Run the operation, retrying it while it fails with a transient error and the attempts are
+    /// not exhausted.
+    ///
+    /// Returns the result of the last attempt.
+    pub fn run<T, F>(&self, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Result<T>,
+    {
+        let mut delay = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(e)
+                    if attempt < self.attempts
+                        && e.as_service_error().map_or(false, |e| e.is_transient()) =>
+                {
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
 mod sc_handle;
 pub mod service;
 pub mod service_control_handler;
 pub mod service_manager;
+pub mod service_notify;
 #[macro_use]
 pub mod service_dispatcher;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 mod double_nul_terminated;
+#[cfg(feature = "serde")]
+mod serde_util;
 mod shell_escape;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_error_classification() {
+        let error = Error::Winapi(std::io::Error::from_raw_os_error(
+            ERROR_SERVICE_MARKED_FOR_DELETE as i32,
+        ));
+        assert_eq!(
+            error.as_service_error(),
+            Some(ServiceError::MarkedForDelete)
+        );
+
+        assert_eq!(
+            Error::ServiceAlreadyRunning.as_service_error(),
+            Some(ServiceError::AlreadyRunning)
+        );
+        assert_eq!(Error::Timeout.as_service_error(), None);
+
+        let unclassified = Error::Winapi(std::io::Error::from_raw_os_error(5000));
+        assert_eq!(unclassified.as_service_error(), None);
+        assert_eq!(unclassified.raw_os_error(), Some(5000));
+    }
+
+    #[test]
+    fn test_error_into_io_error() {
+        let io_error = std::io::Error::from(Error::ServiceAlreadyRunning);
+        assert_eq!(
+            io_error.raw_os_error(),
+            Some(ERROR_SERVICE_ALREADY_RUNNING as i32)
+        );
+
+        let io_error = std::io::Error::from(Error::Winapi(std::io::Error::from_raw_os_error(
+            ERROR_ACCESS_DENIED as i32,
+        )));
+        assert_eq!(io_error.raw_os_error(), Some(ERROR_ACCESS_DENIED as i32));
+
+        let io_error = std::io::Error::from(Error::ArgumentHasNulByte("service name"));
+        assert_eq!(io_error.kind(), std::io::ErrorKind::InvalidInput);
+
+        let io_error = std::io::Error::from(Error::StopTimeout);
+        assert_eq!(io_error.kind(), std::io::ErrorKind::TimedOut);
+
+        let io_error = std::io::Error::from(Error::NotStartedByServiceController);
+        assert_eq!(
+            io_error.raw_os_error(),
+            Some(ERROR_FAILED_SERVICE_CONTROLLER_CONNECT as i32)
+        );
+    }
+
+    #[test]
+    fn test_error_is_access_denied() {
+        let error = Error::Winapi(std::io::Error::from_raw_os_error(
+            ERROR_ACCESS_DENIED as i32,
+        ));
+        assert!(error.is_access_denied());
+        assert!(!Error::Timeout.is_access_denied());
+    }
+
+    #[test]
+    fn test_retry_policy_retries_transient_errors() {
+        let retry_policy = RetryPolicy::new(3, std::time::Duration::ZERO);
+        let transient_error = || {
+            Error::Winapi(std::io::Error::from_raw_os_error(
+                ERROR_SERVICE_DATABASE_LOCKED as i32,
+            ))
+        };
+
+        let mut attempts = 0;
+        let result = retry_policy.run(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(transient_error())
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut attempts = 0;
+        let result: Result<()> = retry_policy.run(|| {
+            attempts += 1;
+            Err(transient_error())
+        });
+        assert_eq!(
+            result.unwrap_err().as_service_error(),
+            Some(ServiceError::DatabaseLocked)
+        );
+        assert_eq!(attempts, 3);
+
+        let mut attempts = 0;
+        let result: Result<()> = retry_policy.run(|| {
+            attempts += 1;
+            Err(Error::Winapi(std::io::Error::from_raw_os_error(
+                ERROR_ACCESS_DENIED as i32,
+            )))
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
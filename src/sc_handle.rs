@@ -1,21 +1,52 @@
 use windows_sys::Win32::{Security, System::Services};
 
 /// A handle holder that wraps a low level [`Security::SC_HANDLE`].
-pub(crate) struct ScHandle(Security::SC_HANDLE);
+///
+/// The handle is either owned and closed on drop, or borrowed from the caller and left open.
+pub(crate) struct ScHandle {
+    handle: Security::SC_HANDLE,
+    owned: bool,
+}
 
 impl ScHandle {
+    /// Take ownership of the handle, closing it on drop.
     pub(crate) unsafe fn new(handle: Security::SC_HANDLE) -> Self {
-        ScHandle(handle)
+        ScHandle {
+            handle,
+            owned: true,
+        }
+    }
+
+    /// Borrow the handle owned elsewhere, which is never closed by [`ScHandle`].
+    ///
+    /// The handle must remain open for as long as [`ScHandle`] is used.
+    pub(crate) unsafe fn borrowed(handle: Security::SC_HANDLE) -> Self {
+        ScHandle {
+            handle,
+            owned: false,
+        }
     }
 
     /// Returns underlying [`Security::SC_HANDLE`].
     pub(crate) fn raw_handle(&self) -> Security::SC_HANDLE {
-        self.0
+        self.handle
+    }
+
+    /// Returns `true` if the handle is closed when [`ScHandle`] is dropped.
+    pub(crate) fn is_owned(&self) -> bool {
+        self.owned
     }
 }
 
 impl Drop for ScHandle {
     fn drop(&mut self) {
-        unsafe { Services::CloseServiceHandle(self.0) };
+        if self.owned {
+            unsafe { Services::CloseServiceHandle(self.handle) };
+        }
     }
 }
+
+// The service control manager handles can be used from multiple threads concurrently, the
+// system serializes the operations on the services database as needed.
+unsafe impl Send for ScHandle {}
+unsafe impl Sync for ScHandle {}
@@ -0,0 +1,120 @@
+//! Helpers for serializing the fields that do not map to serde data model as desired.
+//!
+//! Durations are represented as milliseconds, and `OsString`s as UTF-8 strings. The `OsString`s
+//! that are not valid Unicode are converted lossily, replacing invalid sequences with
+//! U+FFFD REPLACEMENT CHARACTER.
+
+pub mod duration_millis {
+    use std::time::Duration;
+
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+pub mod os_string {
+    use std::ffi::OsString;
+
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(s: &OsString, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&s.to_string_lossy())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OsString, D::Error> {
+        String::deserialize(deserializer).map(OsString::from)
+    }
+}
+
+pub mod option_os_string {
+    use std::ffi::OsString;
+
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        s: &Option<OsString>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match s {
+            Some(s) => serializer.serialize_some(&s.to_string_lossy()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<OsString>, D::Error> {
+        Option::<String>::deserialize(deserializer).map(|s| s.map(OsString::from))
+    }
+}
+
+pub mod vec_os_string {
+    use std::ffi::OsString;
+
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &[OsString], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(v.iter().map(|s| s.to_string_lossy()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<OsString>, D::Error> {
+        Vec::<String>::deserialize(deserializer)
+            .map(|v| v.into_iter().map(OsString::from).collect())
+    }
+}
+
+pub mod option_duration_millis {
+    use std::time::Duration;
+
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => {
+                serializer.serialize_some(&u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+    }
+}
+
+pub mod option_vec_os_string {
+    use std::ffi::OsString;
+
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        v: &Option<Vec<OsString>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match v {
+            Some(v) => serializer
+                .serialize_some(&v.iter().map(|s| s.to_string_lossy()).collect::<Vec<_>>()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<OsString>>, D::Error> {
+        Option::<Vec<String>>::deserialize(deserializer)
+            .map(|v| v.map(|v| v.into_iter().map(OsString::from).collect()))
+    }
+}
@@ -2,16 +2,22 @@ use std::borrow::Cow;
 use std::ffi::{OsStr, OsString};
 use std::os::raw::c_void;
 use std::os::windows::ffi::{OsStrExt, OsStringExt};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::ptr;
-use std::time::Duration;
-use std::{io, mem};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::{io, iter, mem, thread};
 
 use widestring::{error::ContainsNul, WideCStr, WideCString, WideString};
 use windows_sys::{
     core::GUID,
+    Wdk::System::SystemServices::RtlValidRelativeSecurityDescriptor,
     Win32::{
-        Foundation::{ERROR_SERVICE_SPECIFIC_ERROR, NO_ERROR},
+        Foundation::{
+            ERROR_INSUFFICIENT_BUFFER, ERROR_INVALID_LEVEL, ERROR_INVALID_SECURITY_DESCR,
+            ERROR_INVALID_SERVICE_CONTROL, ERROR_MORE_DATA, ERROR_SERVICE_ALREADY_RUNNING,
+            ERROR_SERVICE_SPECIFIC_ERROR, NO_ERROR,
+        },
         Security,
         Storage::FileSystem,
         System::{Power, RemoteDesktop, Services, SystemServices, Threading::INFINITE},
@@ -20,12 +26,20 @@ use windows_sys::{
 };
 
 use crate::sc_handle::ScHandle;
+use crate::service_notify::{ServiceNotifyMask, StatusChangeSubscription};
 use crate::shell_escape;
 use crate::{double_nul_terminated, Error};
 
+pub mod trigger;
+
 bitflags::bitflags! {
     /// Enum describing the types of Windows services.
     #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde_crate::Serialize, serde_crate::Deserialize),
+        serde(crate = "serde_crate", transparent)
+    )]
     pub struct ServiceType: u32 {
         /// File system driver service.
         const FILE_SYSTEM_DRIVER = Services::SERVICE_FILE_SYSTEM_DRIVER;
@@ -40,12 +54,39 @@ bitflags::bitflags! {
         const SHARE_PROCESS = Services::SERVICE_WIN32_SHARE_PROCESS;
 
         /// The service runs in its own process under the logged-on user account.
+        ///
+        /// The installed service is a template, the system creates an instance of it for each
+        /// user that logs on. Includes [`ServiceType::OWN_PROCESS`].
         const USER_OWN_PROCESS = Services::SERVICE_USER_OWN_PROCESS;
 
         /// The service shares a process with one or more other services that run under the logged-on user account.
+        ///
+        /// The installed service is a template, the system creates an instance of it for each
+        /// user that logs on. Includes [`ServiceType::SHARE_PROCESS`].
         const USER_SHARE_PROCESS = Services::SERVICE_USER_SHARE_PROCESS;
 
+        /// The service runs under the logged-on user account.
+        /// Included by [`ServiceType::USER_OWN_PROCESS`] and [`ServiceType::USER_SHARE_PROCESS`].
+        const USER_SERVICE = SystemServices::SERVICE_USER_SERVICE;
+
+        /// The service is an instance of a user service template, created by the system for the
+        /// user that logged on.
+        ///
+        /// The instances are named after the template followed by an underscore and the
+        /// identifier of the logon session, such as `WpnUserService_3a4b5`. Their status and config
+        /// are queried and the instances are started or stopped like any other service, see
+        /// [`ServiceManager::enumerate_user_service_instances`]. The instances cannot be created
+        /// directly.
+        ///
+        /// [`ServiceManager::enumerate_user_service_instances`]: super::service_manager::ServiceManager::enumerate_user_service_instances
+        const USER_SERVICE_INSTANCE = SystemServices::SERVICE_USERSERVICE_INSTANCE;
+
         /// The service can be interactive.
+        ///
+        /// This modifier is deprecated and only valid in combination with
+        /// [`ServiceType::OWN_PROCESS`] or [`ServiceType::SHARE_PROCESS`] for the services
+        /// running as LocalSystem. Interactive services are not able to interact with the user
+        /// since Windows Vista, because the services run in an isolated session.
         const INTERACTIVE_PROCESS = SystemServices::SERVICE_INTERACTIVE_PROCESS;
     }
 }
@@ -72,12 +113,27 @@ bitflags::bitflags! {
         /// Can delete the service
         const DELETE = FileSystem::DELETE;
 
+        /// Can read the security descriptor of the service, except for the SACL
+        const READ_CONTROL = FileSystem::READ_CONTROL;
+
+        /// Can change the DACL of the service security descriptor
+        const WRITE_DAC = FileSystem::WRITE_DAC;
+
+        /// Can change the owner and the primary group of the service security descriptor
+        const WRITE_OWNER = FileSystem::WRITE_OWNER;
+
+        /// Can query and change the SACL of the service security descriptor
+        const ACCESS_SYSTEM_SECURITY = SystemServices::ACCESS_SYSTEM_SECURITY;
+
         /// Can query the services configuration
         const QUERY_CONFIG = Services::SERVICE_QUERY_CONFIG;
 
         /// Can change the services configuration
         const CHANGE_CONFIG = Services::SERVICE_CHANGE_CONFIG;
 
+        /// Can enumerate the services that depend on the service
+        const ENUMERATE_DEPENDENTS = Services::SERVICE_ENUMERATE_DEPENDENTS;
+
         /// Can use user-defined control codes
         const USER_DEFINED_CONTROL = Services::SERVICE_USER_DEFINED_CONTROL;
 
@@ -86,8 +142,31 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Flags describing the parts of the service security descriptor to query or change
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct SecurityInformation: u32 {
+        /// The owner of the object
+        const OWNER = Security::OWNER_SECURITY_INFORMATION;
+
+        /// The primary group of the object
+        const GROUP = Security::GROUP_SECURITY_INFORMATION;
+
+        /// The discretionary access control list (DACL) of the object
+        const DACL = Security::DACL_SECURITY_INFORMATION;
+
+        /// The system access control list (SACL) of the object
+        const SACL = Security::SACL_SECURITY_INFORMATION;
+    }
+}
+
 /// Enum describing the start options for windows services.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 #[repr(u32)]
 pub enum ServiceStartType {
     /// Autostart on system startup
@@ -125,11 +204,25 @@ impl ServiceStartType {
 ///
 /// See <https://msdn.microsoft.com/en-us/library/windows/desktop/ms682450(v=vs.85).aspx>
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 #[repr(u32)]
 pub enum ServiceErrorControl {
+    /// Log the error and restart the system with the last-known-good configuration. If the
+    /// last-known-good configuration is already being started, the boot fails.
     Critical = Services::SERVICE_ERROR_CRITICAL,
+
+    /// Ignore the error and continue the startup.
     Ignore = Services::SERVICE_ERROR_IGNORE,
+
+    /// Log the error and continue the startup.
     Normal = Services::SERVICE_ERROR_NORMAL,
+
+    /// Log the error and restart the system with the last-known-good configuration. If the
+    /// last-known-good configuration is already being started, the startup continues.
     Severe = Services::SERVICE_ERROR_SEVERE,
 }
 
@@ -150,10 +243,34 @@ impl ServiceErrorControl {
 }
 
 /// Service dependency descriptor
+///
+/// A service can depend on other services or on load ordering groups. Services and groups share
+/// the same namespace, so the group names are prefixed with `+` (`SC_GROUP_IDENTIFIER`) when
+/// passed to the system. See [`ServiceDependency::to_system_identifier`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::ffi::OsString;
+/// use windows_service::service::ServiceDependency;
+///
+/// let dependencies = vec![
+///     ServiceDependency::Service(OsString::from("netlogon")),
+///     ServiceDependency::Group(OsString::from("TDI")),
+/// ];
+/// assert_eq!(dependencies[1].to_system_identifier(), "+TDI");
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum ServiceDependency {
-    Service(OsString),
-    Group(OsString),
+    /// Depends on the service with the given name.
+    Service(#[cfg_attr(feature = "serde", serde(with = "crate::serde_util::os_string"))] OsString),
+    /// Depends on at least one service of the load ordering group with the given name.
+    Group(#[cfg_attr(feature = "serde", serde(with = "crate::serde_util::os_string"))] OsString),
 }
 
 impl ServiceDependency {
@@ -189,6 +306,11 @@ impl ServiceDependency {
 
 /// Enum describing the types of actions that the service control manager can perform.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 #[repr(i32)]
 pub enum ServiceActionType {
     None = Services::SC_ACTION_NONE,
@@ -217,16 +339,20 @@ impl ServiceActionType {
 ///
 /// See <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-sc_action>
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct ServiceAction {
     /// The action to be performed.
     pub action_type: ServiceActionType,
 
     /// The time to wait before performing the specified action
     ///
-    /// # Panics
-    ///
-    /// Converting this to the FFI form will panic if the delay is too large to fit as milliseconds
-    /// in a `u32`.
+    /// Converting this to the FFI form fails if the delay does not fit as milliseconds in a `u32`,
+    /// excluding `INFINITE`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::duration_millis"))]
     pub delay: Duration,
 }
 
@@ -239,24 +365,37 @@ impl ServiceAction {
         })
     }
 
-    pub fn to_raw(&self) -> Services::SC_ACTION {
-        Services::SC_ACTION {
+    /// Convert the action to the FFI form.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidServiceInfo`] if the delay does not fit as milliseconds in a `u32`,
+    /// excluding `INFINITE`.
+    pub fn to_raw(&self) -> crate::Result<Services::SC_ACTION> {
+        Ok(Services::SC_ACTION {
             Type: self.action_type.to_raw(),
-            Delay: u32::try_from(self.delay.as_millis()).expect("Too long delay"),
-        }
+            Delay: finite_millis(self.delay).ok_or(Error::InvalidServiceInfo(
+                "service action delay is too long",
+            ))?,
+        })
     }
 }
 
 /// A enum that represents the reset period for the failure counter.
 ///
-/// # Panics
-///
-/// Converting this to the FFI form will panic if the period is too large to fit as seconds in a
-/// `u32`.
+/// Converting this to the FFI form fails if the period does not fit as seconds in a `u32`,
+/// excluding `INFINITE`, which is reserved for [`ServiceFailureResetPeriod::Never`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum ServiceFailureResetPeriod {
     Never,
-    After(Duration),
+    After(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::duration_millis"))] Duration,
+    ),
 }
 
 impl ServiceFailureResetPeriod {
@@ -267,12 +406,21 @@ impl ServiceFailureResetPeriod {
         }
     }
 
-    pub fn to_raw(&self) -> u32 {
+    /// Convert the reset period to the FFI form in seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidServiceInfo`] if the period does not fit as seconds in a `u32`,
+    /// excluding `INFINITE`.
+    pub fn to_raw(&self) -> crate::Result<u32> {
         match self {
-            ServiceFailureResetPeriod::Never => INFINITE,
-            ServiceFailureResetPeriod::After(duration) => {
-                u32::try_from(duration.as_secs()).expect("Too long reset period")
-            }
+            ServiceFailureResetPeriod::Never => Ok(INFINITE),
+            ServiceFailureResetPeriod::After(duration) => u32::try_from(duration.as_secs())
+                .ok()
+                .filter(|&secs| secs != INFINITE)
+                .ok_or(Error::InvalidServiceInfo(
+                    "failure reset period is too long",
+                )),
         }
     }
 }
@@ -282,6 +430,11 @@ impl ServiceFailureResetPeriod {
 /// Please refer to MSDN for more info:\
 /// <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-_service_failure_actionsw>
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct ServiceFailureActions {
     /// The time after which to reset the failure count to zero if there are no failures, in
     /// seconds.
@@ -292,6 +445,7 @@ pub struct ServiceFailureActions {
     ///
     /// If this value is `None`, the reboot message is unchanged.
     /// If the value is an empty string, the reboot message is deleted and no message is broadcast.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
     pub reboot_msg: Option<OsString>,
 
     /// The command line to execute in response to the `SC_ACTION_RUN_COMMAND` service controller
@@ -299,6 +453,7 @@ pub struct ServiceFailureActions {
     ///
     /// If this value is `None`, the command is unchanged. If the value is an empty string, the
     /// command is deleted and no program is run when the service fails.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
     pub command: Option<OsString>,
 
     /// The array of actions to perform.
@@ -351,175 +506,1121 @@ impl ServiceFailureActions {
     }
 }
 
-/// A struct that describes the service.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ServiceInfo {
-    /// Service name
-    pub name: OsString,
+/// Enum describing the types of events that trigger the service action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServiceTriggerType {
+    /// The device of the specified device interface class arrives or is present at startup.
+    DeviceInterfaceArrival,
+    /// The first IP address becomes available or the last IP address becomes unavailable.
+    IpAddressAvailability,
+    /// The computer joins or leaves a domain.
+    DomainJoin,
+    /// A firewall port is opened or closed.
+    FirewallPortEvent,
+    /// A machine or user policy change occurs.
+    GroupPolicy,
+    /// A packet or request arrives on a network protocol.
+    NetworkEndpoint,
+    /// A custom system state change occurs.
+    CustomSystemStateChange,
+    /// An event is generated by an ETW provider.
+    Custom,
+    /// The event is an aggregate of other events, defined by the system.
+    Aggregate,
+    /// Any other trigger type, preserved as the raw `dwTriggerType` value.
+    Other(u32),
+}
 
-    /// User-friendly service name
-    pub display_name: OsString,
+impl ServiceTriggerType {
+    pub fn to_raw(&self) -> u32 {
+        match self {
+            ServiceTriggerType::DeviceInterfaceArrival => {
+                Services::SERVICE_TRIGGER_TYPE_DEVICE_INTERFACE_ARRIVAL
+            }
+            ServiceTriggerType::IpAddressAvailability => {
+                Services::SERVICE_TRIGGER_TYPE_IP_ADDRESS_AVAILABILITY
+            }
+            ServiceTriggerType::DomainJoin => Services::SERVICE_TRIGGER_TYPE_DOMAIN_JOIN,
+            ServiceTriggerType::FirewallPortEvent => {
+                Services::SERVICE_TRIGGER_TYPE_FIREWALL_PORT_EVENT
+            }
+            ServiceTriggerType::GroupPolicy => Services::SERVICE_TRIGGER_TYPE_GROUP_POLICY,
+            ServiceTriggerType::NetworkEndpoint => Services::SERVICE_TRIGGER_TYPE_NETWORK_ENDPOINT,
+            ServiceTriggerType::CustomSystemStateChange => {
+                Services::SERVICE_TRIGGER_TYPE_CUSTOM_SYSTEM_STATE_CHANGE
+            }
+            ServiceTriggerType::Custom => Services::SERVICE_TRIGGER_TYPE_CUSTOM,
+            ServiceTriggerType::Aggregate => Services::SERVICE_TRIGGER_TYPE_AGGREGATE,
+            ServiceTriggerType::Other(raw) => *raw,
+        }
+    }
 
-    /// The service type
-    pub service_type: ServiceType,
+    /// Parse the raw `dwTriggerType` value.
+    ///
+    /// Unexpected values are preserved as [`ServiceTriggerType::Other`].
+    pub fn from_raw(raw: u32) -> ServiceTriggerType {
+        match raw {
+            Services::SERVICE_TRIGGER_TYPE_DEVICE_INTERFACE_ARRIVAL => {
+                ServiceTriggerType::DeviceInterfaceArrival
+            }
+            Services::SERVICE_TRIGGER_TYPE_IP_ADDRESS_AVAILABILITY => {
+                ServiceTriggerType::IpAddressAvailability
+            }
+            Services::SERVICE_TRIGGER_TYPE_DOMAIN_JOIN => ServiceTriggerType::DomainJoin,
+            Services::SERVICE_TRIGGER_TYPE_FIREWALL_PORT_EVENT => {
+                ServiceTriggerType::FirewallPortEvent
+            }
+            Services::SERVICE_TRIGGER_TYPE_GROUP_POLICY => ServiceTriggerType::GroupPolicy,
+            Services::SERVICE_TRIGGER_TYPE_NETWORK_ENDPOINT => ServiceTriggerType::NetworkEndpoint,
+            Services::SERVICE_TRIGGER_TYPE_CUSTOM_SYSTEM_STATE_CHANGE => {
+                ServiceTriggerType::CustomSystemStateChange
+            }
+            Services::SERVICE_TRIGGER_TYPE_CUSTOM => ServiceTriggerType::Custom,
+            Services::SERVICE_TRIGGER_TYPE_AGGREGATE => ServiceTriggerType::Aggregate,
+            _ => ServiceTriggerType::Other(raw),
+        }
+    }
+}
 
-    /// The service startup options
-    pub start_type: ServiceStartType,
+/// Enum describing the action taken when the trigger event occurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ServiceTriggerAction {
+    /// Start the service.
+    ServiceStart = Services::SERVICE_TRIGGER_ACTION_SERVICE_START,
+    /// Stop the service.
+    ServiceStop = Services::SERVICE_TRIGGER_ACTION_SERVICE_STOP,
+}
 
-    /// The severity of the error, and action taken, if this service fails to start.
-    pub error_control: ServiceErrorControl,
+impl ServiceTriggerAction {
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
+    }
 
-    /// Path to the service binary
-    pub executable_path: PathBuf,
+    pub fn from_raw(raw: u32) -> Result<ServiceTriggerAction, ParseRawError> {
+        match raw {
+            x if x == ServiceTriggerAction::ServiceStart.to_raw() => {
+                Ok(ServiceTriggerAction::ServiceStart)
+            }
+            x if x == ServiceTriggerAction::ServiceStop.to_raw() => {
+                Ok(ServiceTriggerAction::ServiceStop)
+            }
+            _ => Err(ParseRawError::InvalidInteger(raw)),
+        }
+    }
+}
 
-    /// Launch arguments passed to `main` when system starts the service.
-    /// This is not the same as arguments passed to `service_main`.
-    pub launch_arguments: Vec<OsString>,
+/// Enum describing the subtype of the trigger event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServiceTriggerSubtype {
+    /// The first IP address becomes available.
+    FirstIpAddressArrival,
+    /// The last IP address becomes unavailable.
+    LastIpAddressRemoval,
+    /// The computer joins a domain.
+    DomainJoin,
+    /// The computer leaves a domain.
+    DomainLeave,
+    /// A firewall port is opened.
+    FirewallPortOpen,
+    /// A firewall port is closed.
+    FirewallPortClose,
+    /// A machine policy is present or changes.
+    MachinePolicyPresent,
+    /// A user policy is present or changes.
+    UserPolicyPresent,
+    /// A request arrives on a named pipe.
+    NamedPipeEvent,
+    /// A request arrives on an RPC interface.
+    RpcInterfaceEvent,
+    /// A custom system state change occurs.
+    CustomSystemStateChange,
+    /// Any other subtype, such as the device interface class or the ETW provider, identified by the
+    /// GUID represented as `u128`.
+    Other(u128),
+}
 
-    /// Service dependencies
-    pub dependencies: Vec<ServiceDependency>,
+impl ServiceTriggerSubtype {
+    pub fn from_guid(guid: &GUID) -> ServiceTriggerSubtype {
+        match guid {
+            x if is_equal_guid(x, &trigger::NETWORK_MANAGER_FIRST_IP_ADDRESS_ARRIVAL_GUID) => {
+                ServiceTriggerSubtype::FirstIpAddressArrival
+            }
+            x if is_equal_guid(x, &trigger::NETWORK_MANAGER_LAST_IP_ADDRESS_REMOVAL_GUID) => {
+                ServiceTriggerSubtype::LastIpAddressRemoval
+            }
+            x if is_equal_guid(x, &trigger::DOMAIN_JOIN_GUID) => ServiceTriggerSubtype::DomainJoin,
+            x if is_equal_guid(x, &trigger::DOMAIN_LEAVE_GUID) => {
+                ServiceTriggerSubtype::DomainLeave
+            }
+            x if is_equal_guid(x, &trigger::FIREWALL_PORT_OPEN_GUID) => {
+                ServiceTriggerSubtype::FirewallPortOpen
+            }
+            x if is_equal_guid(x, &trigger::FIREWALL_PORT_CLOSE_GUID) => {
+                ServiceTriggerSubtype::FirewallPortClose
+            }
+            x if is_equal_guid(x, &trigger::MACHINE_POLICY_PRESENT_GUID) => {
+                ServiceTriggerSubtype::MachinePolicyPresent
+            }
+            x if is_equal_guid(x, &trigger::USER_POLICY_PRESENT_GUID) => {
+                ServiceTriggerSubtype::UserPolicyPresent
+            }
+            x if is_equal_guid(x, &trigger::NAMED_PIPE_EVENT_GUID) => {
+                ServiceTriggerSubtype::NamedPipeEvent
+            }
+            x if is_equal_guid(x, &trigger::RPC_INTERFACE_EVENT_GUID) => {
+                ServiceTriggerSubtype::RpcInterfaceEvent
+            }
+            x if is_equal_guid(x, &trigger::CUSTOM_SYSTEM_STATE_CHANGE_EVENT_GUID) => {
+                ServiceTriggerSubtype::CustomSystemStateChange
+            }
+            x => ServiceTriggerSubtype::Other(guid_to_u128(x)),
+        }
+    }
 
-    /// Account to use for running the service.
-    /// for example: NT Authority\System.
-    /// use `None` to run as LocalSystem.
-    pub account_name: Option<OsString>,
+    pub fn to_guid(&self) -> GUID {
+        match self {
+            ServiceTriggerSubtype::FirstIpAddressArrival => {
+                trigger::NETWORK_MANAGER_FIRST_IP_ADDRESS_ARRIVAL_GUID
+            }
+            ServiceTriggerSubtype::LastIpAddressRemoval => {
+                trigger::NETWORK_MANAGER_LAST_IP_ADDRESS_REMOVAL_GUID
+            }
+            ServiceTriggerSubtype::DomainJoin => trigger::DOMAIN_JOIN_GUID,
+            ServiceTriggerSubtype::DomainLeave => trigger::DOMAIN_LEAVE_GUID,
+            ServiceTriggerSubtype::FirewallPortOpen => trigger::FIREWALL_PORT_OPEN_GUID,
+            ServiceTriggerSubtype::FirewallPortClose => trigger::FIREWALL_PORT_CLOSE_GUID,
+            ServiceTriggerSubtype::MachinePolicyPresent => trigger::MACHINE_POLICY_PRESENT_GUID,
+            ServiceTriggerSubtype::UserPolicyPresent => trigger::USER_POLICY_PRESENT_GUID,
+            ServiceTriggerSubtype::NamedPipeEvent => trigger::NAMED_PIPE_EVENT_GUID,
+            ServiceTriggerSubtype::RpcInterfaceEvent => trigger::RPC_INTERFACE_EVENT_GUID,
+            ServiceTriggerSubtype::CustomSystemStateChange => {
+                trigger::CUSTOM_SYSTEM_STATE_CHANGE_EVENT_GUID
+            }
+            ServiceTriggerSubtype::Other(guid) => GUID::from_u128(*guid),
+        }
+    }
+}
 
-    /// Account password.
-    /// For system accounts this should normally be `None`.
-    pub account_password: Option<OsString>,
+/// Enum describing the trigger-specific data items.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ServiceTriggerDataItem {
+    /// Binary data.
+    Binary(Vec<u8>),
+    /// String data, such as the hardware ids of the device or the firewall port.
+    ///
+    /// The system stores the strings as `REG_MULTI_SZ`, a single string is represented as a list
+    /// with one element.
+    String(Vec<OsString>),
+    /// The level of the ETW event.
+    Level(u8),
+    /// The ETW event must match any of the keyword bits.
+    KeywordAny(u64),
+    /// The ETW event must match all of the keyword bits.
+    KeywordAll(u64),
+    /// Any other data type, preserved as the raw `dwDataType` value and the raw data.
+    Other { data_type: u32, data: Vec<u8> },
 }
 
-/// Same as `ServiceInfo` but with fields that are compatible with the Windows API.
-pub(crate) struct RawServiceInfo {
-    /// Service name
-    pub name: WideCString,
+impl ServiceTriggerDataItem {
+    /// Tries to parse a `SERVICE_TRIGGER_SPECIFIC_DATA_ITEM` into Rust [`ServiceTriggerDataItem`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is too short for the data type.
+    ///
+    /// # Safety
+    ///
+    /// The `pData` must be a valid pointer to `cbData` bytes.
+    pub unsafe fn from_raw(
+        raw: Services::SERVICE_TRIGGER_SPECIFIC_DATA_ITEM,
+    ) -> Result<ServiceTriggerDataItem, ParseRawError> {
+        let data: &[u8] = if raw.pData.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(raw.pData, usize::try_from(raw.cbData).unwrap())
+        };
 
-    /// User-friendly service name
-    pub display_name: WideCString,
+        match raw.dwDataType {
+            Services::SERVICE_TRIGGER_DATA_TYPE_BINARY => {
+                Ok(ServiceTriggerDataItem::Binary(data.to_vec()))
+            }
+            Services::SERVICE_TRIGGER_DATA_TYPE_STRING => {
+                let wide: Vec<u16> = data
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .collect();
+                // The list is terminated by an empty string, the terminators themselves may be
+                // missing when the data was not written by the system.
+                let strings = wide
+                    .split(|c| *c == 0)
+                    .take_while(|s| !s.is_empty())
+                    .map(OsString::from_wide)
+                    .collect();
+                Ok(ServiceTriggerDataItem::String(strings))
+            }
+            Services::SERVICE_TRIGGER_DATA_TYPE_LEVEL => match data.first() {
+                Some(level) => Ok(ServiceTriggerDataItem::Level(*level)),
+                None => Err(ParseRawError::InvalidInteger(raw.cbData)),
+            },
+            Services::SERVICE_TRIGGER_DATA_TYPE_KEYWORD_ANY
+            | Services::SERVICE_TRIGGER_DATA_TYPE_KEYWORD_ALL => {
+                let keyword = data
+                    .get(..8)
+                    .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(ParseRawError::InvalidInteger(raw.cbData))?;
+                if raw.dwDataType == Services::SERVICE_TRIGGER_DATA_TYPE_KEYWORD_ANY {
+                    Ok(ServiceTriggerDataItem::KeywordAny(keyword))
+                } else {
+                    Ok(ServiceTriggerDataItem::KeywordAll(keyword))
+                }
+            }
+            data_type => Ok(ServiceTriggerDataItem::Other {
+                data_type,
+                data: data.to_vec(),
+            }),
+        }
+    }
 
-    /// The service type
-    pub service_type: u32,
+    /// Returns the raw data type and the data in the format expected by the system.
+    fn to_raw_data(&self) -> crate::Result<(u32, Vec<u8>)> {
+        match self {
+            ServiceTriggerDataItem::Binary(data) => {
+                Ok((Services::SERVICE_TRIGGER_DATA_TYPE_BINARY, data.clone()))
+            }
+            ServiceTriggerDataItem::String(strings) => {
+                let mut wide = Vec::new();
+                for s in strings {
+                    if s.is_empty() {
+                        return Err(Error::InvalidServiceInfo(
+                            "service trigger string data contains an empty string",
+                        ));
+                    }
+                    let s = WideCString::from_os_str(s)
+                        .map_err(|_| Error::ArgumentHasNulByte("service trigger string data"))?;
+                    wide.extend_from_slice(s.as_slice_with_nul());
+                }
+                // The list is terminated by an empty string.
+                wide.push(0);
+                let data = wide.iter().flat_map(|c| c.to_le_bytes()).collect();
+                Ok((Services::SERVICE_TRIGGER_DATA_TYPE_STRING, data))
+            }
+            ServiceTriggerDataItem::Level(level) => {
+                Ok((Services::SERVICE_TRIGGER_DATA_TYPE_LEVEL, vec![*level]))
+            }
+            ServiceTriggerDataItem::KeywordAny(keyword) => Ok((
+                Services::SERVICE_TRIGGER_DATA_TYPE_KEYWORD_ANY,
+                keyword.to_le_bytes().to_vec(),
+            )),
+            ServiceTriggerDataItem::KeywordAll(keyword) => Ok((
+                Services::SERVICE_TRIGGER_DATA_TYPE_KEYWORD_ALL,
+                keyword.to_le_bytes().to_vec(),
+            )),
+            ServiceTriggerDataItem::Other { data_type, data } => Ok((*data_type, data.clone())),
+        }
+    }
+}
 
-    /// The service startup options
-    pub start_type: u32,
+/// A struct that describes the event that triggers the service action.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServiceTrigger {
+    /// The type of the trigger event.
+    pub trigger_type: ServiceTriggerType,
 
-    /// The severity of the error, and action taken, if this service fails to start.
-    pub error_control: u32,
+    /// The action to take when the trigger event occurs.
+    pub action: ServiceTriggerAction,
 
-    /// Path to the service binary with arguments appended
-    pub launch_command: WideCString,
+    /// The subtype of the trigger event, such as the device interface class for
+    /// [`ServiceTriggerType::DeviceInterfaceArrival`].
+    pub trigger_subtype: Option<ServiceTriggerSubtype>,
 
-    /// Service dependencies
-    pub dependencies: Option<WideString>,
+    /// The trigger-specific data narrowing down the events that trigger the action.
+    pub data_items: Vec<ServiceTriggerDataItem>,
+}
 
-    /// Account to use for running the service.
-    /// for example: NT Authority\System.
-    /// use `None` to run as LocalSystem.
-    pub account_name: Option<WideCString>,
+impl ServiceTrigger {
+    /// Tries to parse a `SERVICE_TRIGGER` into Rust [`ServiceTrigger`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the fields hold an invalid value.
+    ///
+    /// # Safety
+    ///
+    /// The pointers in `raw` must be either null or valid pointers to the number of elements
+    /// specified in the corresponding fields.
+    pub unsafe fn from_raw(
+        raw: Services::SERVICE_TRIGGER,
+    ) -> Result<ServiceTrigger, ParseRawError> {
+        let trigger_subtype = ptr::NonNull::new(raw.pTriggerSubtype)
+            .map(|guid| ServiceTriggerSubtype::from_guid(&*guid.as_ptr()));
+        let data_items = if raw.pDataItems.is_null() {
+            Vec::new()
+        } else {
+            (0..raw.cDataItems)
+                .map(|i| ServiceTriggerDataItem::from_raw(*raw.pDataItems.offset(i as isize)))
+                .collect::<Result<Vec<_>, _>>()?
+        };
 
-    /// Account password.
-    /// For system accounts this should normally be `None`.
-    pub account_password: Option<WideCString>,
+        Ok(ServiceTrigger {
+            trigger_type: ServiceTriggerType::from_raw(raw.dwTriggerType),
+            action: ServiceTriggerAction::from_raw(raw.dwAction)?,
+            trigger_subtype,
+            data_items,
+        })
+    }
 }
 
-impl RawServiceInfo {
-    pub fn new(service_info: &ServiceInfo) -> crate::Result<Self> {
-        let service_name = WideCString::from_os_str(&service_info.name)
-            .map_err(|_| Error::ArgumentHasNulByte("service name"))?;
-        let display_name = WideCString::from_os_str(&service_info.display_name)
-            .map_err(|_| Error::ArgumentHasNulByte("display name"))?;
-        let account_name = to_wide(service_info.account_name.as_ref())
-            .map_err(|_| Error::ArgumentHasNulByte("account name"))?;
-        let account_password = to_wide(service_info.account_password.as_ref())
-            .map_err(|_| Error::ArgumentHasNulByte("account password"))?;
-
-        // escape executable path and arguments and combine them into a single command
-        let mut launch_command_buffer = WideString::new();
-        if service_info
-            .service_type
-            .intersects(ServiceType::KERNEL_DRIVER | ServiceType::FILE_SYSTEM_DRIVER)
-        {
-            // drivers do not support launch arguments
-            if !service_info.launch_arguments.is_empty() {
-                return Err(Error::LaunchArgumentsNotSupported);
-            }
+/// A struct that describes the triggers of the service.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ServiceTriggerInfo {
+    /// The events that trigger the service actions.
+    pub triggers: Vec<ServiceTrigger>,
+}
 
-            // also the path must not be quoted even if it contains spaces
-            let executable_path = WideCString::from_os_str(&service_info.executable_path)
-                .map_err(|_| Error::ArgumentHasNulByte("executable path"))?;
-            launch_command_buffer.push(executable_path.to_ustring());
+impl ServiceTriggerInfo {
+    /// Tries to parse a `SERVICE_TRIGGER_INFO` into Rust [`ServiceTriggerInfo`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the triggers hold an invalid value.
+    ///
+    /// # Safety
+    ///
+    /// The `pTriggers` must be either null or a valid pointer to `cTriggers` triggers.
+    pub unsafe fn from_raw(
+        raw: Services::SERVICE_TRIGGER_INFO,
+    ) -> crate::Result<ServiceTriggerInfo> {
+        let triggers = if raw.pTriggers.is_null() {
+            Vec::new()
         } else {
-            let executable_path = escape_wide(&service_info.executable_path)
-                .map_err(|_| Error::ArgumentHasNulByte("executable path"))?;
-            launch_command_buffer.push(executable_path);
+            (0..raw.cTriggers)
+                .map(|i| ServiceTrigger::from_raw(*raw.pTriggers.offset(i as isize)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| Error::ParseValue("service trigger", e))?
+        };
+
+        Ok(ServiceTriggerInfo { triggers })
+    }
+}
 
-            for (i, launch_argument) in service_info.launch_arguments.iter().enumerate() {
-                let wide = escape_wide(launch_argument)
-                    .map_err(|_| Error::ArgumentArrayElementHasNulByte("launch argument", i))?;
+/// Same as `ServiceTriggerInfo` but with fields that are compatible with the Windows API.
+///
+/// Owns the nested arrays referenced by `SERVICE_TRIGGER_INFO`.
+struct RawServiceTriggerInfo {
+    _subtypes: Vec<Option<GUID>>,
+    _data: Vec<Vec<u8>>,
+    _data_items: Vec<Vec<Services::SERVICE_TRIGGER_SPECIFIC_DATA_ITEM>>,
+    triggers: Vec<Services::SERVICE_TRIGGER>,
+}
 
-                launch_command_buffer.push_str(" ");
-                launch_command_buffer.push(wide);
+impl RawServiceTriggerInfo {
+    fn new(trigger_info: &ServiceTriggerInfo) -> crate::Result<Self> {
+        let mut subtypes: Vec<Option<GUID>> = trigger_info
+            .triggers
+            .iter()
+            .map(|trigger| trigger.trigger_subtype.map(|subtype| subtype.to_guid()))
+            .collect();
+        let mut data: Vec<Vec<u8>> = Vec::new();
+        let mut data_items = Vec::with_capacity(trigger_info.triggers.len());
+
+        for trigger in &trigger_info.triggers {
+            let mut raw_items = Vec::with_capacity(trigger.data_items.len());
+            for item in &trigger.data_items {
+                let (data_type, mut item_data) = item.to_raw_data()?;
+                // Moving the vector into `data` does not reallocate the memory `pData` refers to.
+                raw_items.push(Services::SERVICE_TRIGGER_SPECIFIC_DATA_ITEM {
+                    dwDataType: data_type,
+                    cbData: u32::try_from(item_data.len()).expect("size must fit in u32"),
+                    pData: item_data.as_mut_ptr(),
+                });
+                data.push(item_data);
             }
+            data_items.push(raw_items);
         }
 
-        // Safety: We are sure launch_command_buffer does not contain nulls
-        let launch_command = unsafe { WideCString::from_ustr_unchecked(launch_command_buffer) };
-
-        let dependency_identifiers: Vec<OsString> = service_info
-            .dependencies
+        let triggers = trigger_info
+            .triggers
             .iter()
-            .map(|dependency| dependency.to_system_identifier())
+            .zip(subtypes.iter_mut())
+            .zip(data_items.iter_mut())
+            .map(
+                |((trigger, subtype), raw_items)| Services::SERVICE_TRIGGER {
+                    dwTriggerType: trigger.trigger_type.to_raw(),
+                    dwAction: trigger.action.to_raw(),
+                    pTriggerSubtype: subtype
+                        .as_mut()
+                        .map_or(ptr::null_mut(), |guid| guid as *mut GUID),
+                    cDataItems: u32::try_from(raw_items.len()).expect("size must fit in u32"),
+                    pDataItems: if raw_items.is_empty() {
+                        ptr::null_mut()
+                    } else {
+                        raw_items.as_mut_ptr()
+                    },
+                },
+            )
             .collect();
-        let joined_dependencies = double_nul_terminated::from_slice(&dependency_identifiers)
-            .map_err(|_| Error::ArgumentHasNulByte("dependency"))?;
 
-        Ok(Self {
-            name: service_name,
-            display_name,
-            service_type: service_info.service_type.bits(),
-            start_type: service_info.start_type.to_raw(),
-            error_control: service_info.error_control.to_raw(),
-            launch_command,
-            dependencies: joined_dependencies,
-            account_name,
-            account_password,
+        Ok(RawServiceTriggerInfo {
+            _subtypes: subtypes,
+            _data: data,
+            _data_items: data_items,
+            triggers,
         })
     }
 }
 
-/// A struct that describes the service.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ServiceConfig {
-    /// The service type
-    pub service_type: ServiceType,
+/// A string holding a secret, such as the account password, which is erased from memory on drop.
+///
+/// The secret is stored as a wide string, which is what the Windows API expects, so that no
+/// further plaintext copies have to be made when passing it to the system.
+///
+/// The conversions from an owned [`String`] or a valid Unicode [`OsString`] erase the original
+/// buffer. Note that the conversions from [`&str`](str) or [`&OsStr`](OsStr) leave the borrowed
+/// buffer untouched, and so does the conversion from an [`OsString`] that is not valid Unicode.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct SecretOsString(Vec<u16>);
+
+impl SecretOsString {
+    /// Create a secret from the given string.
+    pub fn new(s: impl AsRef<OsStr>) -> Self {
+        let s = s.as_ref();
+        // The UTF-16 representation never has more code units than WTF-8 has bytes, so the buffer
+        // is never reallocated leaving the unerased copies of the secret behind.
+        let mut wide = Vec::with_capacity(s.len() + 1);
+        wide.extend(s.encode_wide());
+        SecretOsString(wide)
+    }
 
-    /// The service startup options
-    pub start_type: ServiceStartType,
+    /// Returns a plaintext copy of the secret.
+    pub fn to_os_string(&self) -> OsString {
+        OsString::from_wide(&self.0)
+    }
 
-    /// The severity of the error, and action taken, if this service fails to start.
-    pub error_control: ServiceErrorControl,
+    /// Returns a copy of the secret with the nul terminator appended, or `None` if the secret
+    /// contains a nul.
+    pub(crate) fn to_nul_terminated(&self) -> Option<SecretOsString> {
+        if self.0.contains(&0) {
+            return None;
+        }
+        let mut wide = Vec::with_capacity(self.0.len() + 1);
+        wide.extend_from_slice(&self.0);
+        wide.push(0);
+        Some(SecretOsString(wide))
+    }
 
-    /// Path to the service binary
-    pub executable_path: PathBuf,
+    pub(crate) fn as_ptr(&self) -> *const u16 {
+        self.0.as_ptr()
+    }
+}
 
-    /// Path to the service binary
-    pub load_order_group: Option<OsString>,
+impl Drop for SecretOsString {
+    fn drop(&mut self) {
+        for c in self.0.iter_mut() {
+            // Volatile writes are not optimized away even though the buffer is freed right after.
+            unsafe { ptr::write_volatile(c, 0) };
+        }
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+    }
+}
 
-    /// A unique tag value for this service in the group specified by the load_order_group
-    /// parameter.
-    pub tag_id: u32,
+impl std::fmt::Debug for SecretOsString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretOsString(\"***\")")
+    }
+}
 
-    /// Service dependencies
-    pub dependencies: Vec<ServiceDependency>,
+impl From<OsString> for SecretOsString {
+    fn from(s: OsString) -> Self {
+        match s.into_string() {
+            Ok(s) => SecretOsString::from(s),
+            Err(s) => SecretOsString::new(s),
+        }
+    }
+}
 
-    /// Account to use for running the service.
-    /// for example: NT Authority\System.
-    ///
-    /// This value can be `None` in certain cases, please refer to MSDN for more info:\
-    /// <https://docs.microsoft.com/en-us/windows/desktop/api/winsvc/ns-winsvc-_query_service_configw>
-    pub account_name: Option<OsString>,
+impl From<&OsStr> for SecretOsString {
+    fn from(s: &OsStr) -> Self {
+        SecretOsString::new(s)
+    }
+}
+
+impl From<String> for SecretOsString {
+    fn from(s: String) -> Self {
+        let secret = SecretOsString::new(&s);
+        erase_bytes(s.into_bytes());
+        secret
+    }
+}
+
+impl From<&str> for SecretOsString {
+    fn from(s: &str) -> Self {
+        SecretOsString::new(s)
+    }
+}
+
+/// Deserializes the secret from a string, the secret is never serialized.
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::Deserialize<'de> for SecretOsString {
+    fn deserialize<D: serde_crate::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <String as serde_crate::Deserialize>::deserialize(deserializer)?;
+        // Erases the intermediate plaintext copy.
+        Ok(SecretOsString::from(s))
+    }
+}
+
+/// Erase the plaintext bytes before the buffer is freed.
+fn erase_bytes(mut bytes: Vec<u8>) {
+    for b in bytes.iter_mut() {
+        // Volatile writes are not optimized away even though the buffer is freed right after.
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Enum describing the binary that the system runs for the service.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::ffi::OsString;
+/// use std::path::PathBuf;
+/// use windows_service::service::ServiceBinary;
+///
+/// let executable = ServiceBinary::Executable {
+///     path: PathBuf::from(r"C:\Program Files\My Service\service.exe"),
+///     args: vec![OsString::from("--run-as-service")],
+/// };
+/// let driver = ServiceBinary::RawCommandLine(OsString::from(
+///     r"\SystemRoot\System32\drivers\my_driver.sys",
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum ServiceBinary {
+    /// Path to the service binary and the launch arguments passed to `main` when system starts
+    /// the service.
+    ///
+    /// The path and the arguments are quoted and escaped as needed. Driver paths are never
+    /// quoted and drivers do not support launch arguments.
+    Executable {
+        path: PathBuf,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::vec_os_string"))]
+        args: Vec<OsString>,
+    },
+
+    /// The command line that is passed to the system as is, without quoting or escaping.
+    ///
+    /// This is useful for the paths in a form that the system resolves itself, e.g.
+    /// `\SystemRoot\System32\drivers\my_driver.sys`, or for the hosts that parse their command
+    /// line in an unusual way.
+    RawCommandLine(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::os_string"))] OsString,
+    ),
+}
+
+impl ServiceBinary {
+    /// Build the command line passed to the system as `lpBinaryPathName`.
+    fn to_launch_command(&self, service_type: ServiceType) -> crate::Result<WideCString> {
+        match self {
+            ServiceBinary::Executable { path, args } => launch_command(service_type, path, args),
+            ServiceBinary::RawCommandLine(command_line) => WideCString::from_os_str(command_line)
+                .map_err(|_| Error::ArgumentHasNulByte("command line")),
+        }
+    }
+}
+
+/// A struct that describes the service.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct ServiceInfo {
+    /// Service name
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::os_string"))]
+    pub name: OsString,
+
+    /// User-friendly service name
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::os_string"))]
+    pub display_name: OsString,
+
+    /// The service type
+    pub service_type: ServiceType,
+
+    /// The service startup options
+    pub start_type: ServiceStartType,
+
+    /// The severity of the error, and action taken, if this service fails to start.
+    pub error_control: ServiceErrorControl,
+
+    /// The service binary along with its launch arguments.
+    ///
+    /// Launch arguments are passed to `main` when system starts the service. These are not the
+    /// same as arguments passed to `service_main`.
+    ///
+    /// For driver services this is the image path of the driver, which is often a native NT
+    /// path, such as `\SystemRoot\System32\drivers\my_driver.sys`, that is best passed as
+    /// [`ServiceBinary::RawCommandLine`].
+    pub binary: ServiceBinary,
+
+    /// The load ordering group that the service belongs to.
+    /// Use `None` if the service does not belong to a group.
+    ///
+    /// The system assigns a unique tag within the group to driver services, see
+    /// [`Service::tag_id`].
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
+    pub load_order_group: Option<OsString>,
+
+    /// Service dependencies
+    pub dependencies: Vec<ServiceDependency>,
+
+    /// Account to use for running the service.
+    /// for example: NT Authority\System.
+    /// use `None` to run as LocalSystem.
+    ///
+    /// Must be `None` for driver services.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
+    pub account_name: Option<OsString>,
+
+    /// Account password.
+    /// For system accounts this should normally be `None`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing, default))]
+    pub account_password: Option<SecretOsString>,
+}
+
+/// A builder for [`ServiceInfo`] that validates the combination of the fields.
+///
+/// Fields that are not set default to a service that runs in its own process as LocalSystem,
+/// is started on demand and uses the service name as the display name.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use windows_service::service::{ServiceInfoBuilder, ServiceStartType};
+///
+/// # fn main() -> windows_service::Result<()> {
+/// let service_info = ServiceInfoBuilder::new("my_service", r"C:\path\to\my\service.exe")
+///     .display_name("My service")
+///     .start_type(ServiceStartType::AutoStart)
+///     .launch_arguments(["--run-as-service"])
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServiceInfoBuilder {
+    service_info: ServiceInfo,
+    launch_arguments: Vec<OsString>,
+}
+
+impl ServiceInfoBuilder {
+    /// Create a builder for the service with the given name and path to the service binary.
+    pub fn new(name: impl Into<OsString>, executable_path: impl Into<PathBuf>) -> Self {
+        Self::with_binary(
+            name.into(),
+            ServiceBinary::Executable {
+                path: executable_path.into(),
+                args: Vec::new(),
+            },
+        )
+    }
+
+    /// Create a builder for the service with the given name and the command line that is passed
+    /// to the system as is.
+    ///
+    /// See [`ServiceBinary::RawCommandLine`].
+    pub fn new_raw(name: impl Into<OsString>, command_line: impl Into<OsString>) -> Self {
+        Self::with_binary(
+            name.into(),
+            ServiceBinary::RawCommandLine(command_line.into()),
+        )
+    }
+
+    fn with_binary(name: OsString, binary: ServiceBinary) -> Self {
+        ServiceInfoBuilder {
+            service_info: ServiceInfo {
+                display_name: name.clone(),
+                name,
+                service_type: ServiceType::OWN_PROCESS,
+                start_type: ServiceStartType::OnDemand,
+                error_control: ServiceErrorControl::Normal,
+                binary,
+                load_order_group: None,
+                dependencies: Vec::new(),
+                account_name: None,
+                account_password: None,
+            },
+            launch_arguments: Vec::new(),
+        }
+    }
+
+    /// Set the user-friendly service name.
+    pub fn display_name(mut self, display_name: impl Into<OsString>) -> Self {
+        self.service_info.display_name = display_name.into();
+        self
+    }
+
+    /// Set the service type.
+    pub fn service_type(mut self, service_type: ServiceType) -> Self {
+        self.service_info.service_type = service_type;
+        self
+    }
+
+    /// Set the service startup options.
+    pub fn start_type(mut self, start_type: ServiceStartType) -> Self {
+        self.service_info.start_type = start_type;
+        self
+    }
+
+    /// Set the severity of the error, and action taken, if the service fails to start.
+    pub fn error_control(mut self, error_control: ServiceErrorControl) -> Self {
+        self.service_info.error_control = error_control;
+        self
+    }
+
+    /// Set the launch arguments passed to `main` when system starts the service.
+    pub fn launch_arguments<I, S>(mut self, launch_arguments: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.launch_arguments = launch_arguments.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the load ordering group that the service belongs to.
+    pub fn load_order_group(mut self, load_order_group: impl Into<OsString>) -> Self {
+        self.service_info.load_order_group = Some(load_order_group.into());
+        self
+    }
+
+    /// Set the service dependencies.
+    pub fn dependencies(
+        mut self,
+        dependencies: impl IntoIterator<Item = ServiceDependency>,
+    ) -> Self {
+        self.service_info.dependencies = dependencies.into_iter().collect();
+        self
+    }
+
+    /// Set the account to use for running the service.
+    pub fn account_name(mut self, account_name: impl Into<OsString>) -> Self {
+        self.service_info.account_name = Some(account_name.into());
+        self
+    }
+
+    /// Set the account password.
+    pub fn account_password(mut self, account_password: impl Into<SecretOsString>) -> Self {
+        self.service_info.account_password = Some(account_password.into());
+        self
+    }
+
+    /// Validate the fields and build the [`ServiceInfo`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidServiceInfo`] if the fields are inconsistent, e.g. the account
+    /// password is set without the account name, the account is set for a driver service or
+    /// launch arguments are combined with a raw command line, or
+    /// [`Error::LaunchArgumentsNotSupported`] if launch arguments are set for a driver service.
+    pub fn build(self) -> crate::Result<ServiceInfo> {
+        let mut info = self.service_info;
+        let process_types = ServiceType::OWN_PROCESS | ServiceType::SHARE_PROCESS;
+        let driver_types = ServiceType::KERNEL_DRIVER | ServiceType::FILE_SYSTEM_DRIVER;
+
+        if info.name.is_empty() {
+            return Err(Error::InvalidServiceInfo("service name cannot be empty"));
+        }
+        if info.service_type.contains(process_types) {
+            return Err(Error::InvalidServiceInfo(
+                "service type cannot be both OWN_PROCESS and SHARE_PROCESS",
+            ));
+        }
+        if info.service_type.intersects(process_types) == info.service_type.intersects(driver_types)
+        {
+            return Err(Error::InvalidServiceInfo(
+                "service type must be either a process or a driver type",
+            ));
+        }
+        if info
+            .service_type
+            .contains(ServiceType::USER_SERVICE_INSTANCE)
+        {
+            return Err(Error::InvalidServiceInfo(
+                "user service instances are created by the system",
+            ));
+        }
+        if info.service_type.contains(ServiceType::INTERACTIVE_PROCESS) {
+            if info.service_type.contains(ServiceType::USER_SERVICE) {
+                return Err(Error::InvalidServiceInfo(
+                    "user services cannot be interactive",
+                ));
+            }
+            let runs_as_local_system = info
+                .account_name
+                .as_ref()
+                .map_or(true, |name| is_local_system_account(name));
+            if !runs_as_local_system {
+                return Err(Error::InvalidServiceInfo(
+                    "interactive services must run as LocalSystem",
+                ));
+            }
+        }
+        if !self.launch_arguments.is_empty() {
+            if info.service_type.intersects(driver_types) {
+                return Err(Error::LaunchArgumentsNotSupported);
+            }
+            match info.binary {
+                ServiceBinary::Executable { ref mut args, .. } => *args = self.launch_arguments,
+                ServiceBinary::RawCommandLine(_) => {
+                    return Err(Error::InvalidServiceInfo(
+                        "launch arguments cannot be combined with a raw command line",
+                    ))
+                }
+            }
+        }
+        check_driver_account(&info)?;
+        if info.account_password.is_some() && info.account_name.is_none() {
+            return Err(Error::InvalidServiceInfo(
+                "account password cannot be set without account name",
+            ));
+        }
+
+        Ok(info)
+    }
+}
+
+/// Same as `ServiceInfo` but with fields that are compatible with the Windows API.
+pub(crate) struct RawServiceInfo {
+    /// Service name
+    pub name: WideCString,
+
+    /// User-friendly service name
+    pub display_name: WideCString,
+
+    /// The service type
+    pub service_type: u32,
+
+    /// The service startup options
+    pub start_type: u32,
+
+    /// The severity of the error, and action taken, if this service fails to start.
+    pub error_control: u32,
+
+    /// Path to the service binary with arguments appended
+    pub launch_command: WideCString,
+
+    /// The load ordering group that the service belongs to
+    pub load_order_group: Option<WideCString>,
+
+    /// Service dependencies
+    pub dependencies: Option<WideString>,
+
+    /// Account to use for running the service.
+    /// for example: NT Authority\System.
+    /// use `None` to run as LocalSystem.
+    pub account_name: Option<WideCString>,
+
+    /// Account password.
+    /// For system accounts this should normally be `None`.
+    pub account_password: Option<SecretOsString>,
+}
+
+impl RawServiceInfo {
+    pub fn new(service_info: &ServiceInfo) -> crate::Result<Self> {
+        check_driver_account(service_info)?;
+        let service_name = WideCString::from_os_str(&service_info.name)
+            .map_err(|_| Error::ArgumentHasNulByte("service name"))?;
+        let display_name = WideCString::from_os_str(&service_info.display_name)
+            .map_err(|_| Error::ArgumentHasNulByte("display name"))?;
+        let account_name = to_wide(service_info.account_name.as_ref())
+            .map_err(|_| Error::ArgumentHasNulByte("account name"))?;
+        let account_password = secret_to_wide(service_info.account_password.as_ref())?;
+
+        let launch_command = service_info
+            .binary
+            .to_launch_command(service_info.service_type)?;
+        let load_order_group = to_wide(service_info.load_order_group.as_ref())
+            .map_err(|_| Error::ArgumentHasNulByte("load order group"))?;
+        let joined_dependencies = joined_dependencies(&service_info.dependencies)?;
+
+        Ok(Self {
+            name: service_name,
+            display_name,
+            service_type: service_info.service_type.bits(),
+            start_type: service_info.start_type.to_raw(),
+            error_control: service_info.error_control.to_raw(),
+            launch_command,
+            load_order_group,
+            dependencies: joined_dependencies,
+            account_name,
+            account_password,
+        })
+    }
+}
+
+/// Drivers are loaded by the system, so the account fields must not be set for them.
+/// Returns `true` if the account name refers to the local system account, comparing the names
+/// ignoring case like the system does.
+fn is_local_system_account(account_name: &OsStr) -> bool {
+    account_name.eq_ignore_ascii_case("LocalSystem")
+        || account_name.eq_ignore_ascii_case(".\\LocalSystem")
+}
+
+fn check_driver_account(service_info: &ServiceInfo) -> crate::Result<()> {
+    let is_driver = service_info
+        .service_type
+        .intersects(ServiceType::KERNEL_DRIVER | ServiceType::FILE_SYSTEM_DRIVER);
+    if is_driver && (service_info.account_name.is_some() || service_info.account_password.is_some())
+    {
+        Err(Error::InvalidServiceInfo(
+            "account cannot be set for a driver service",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// A struct that describes the changes to the configuration of an existing service.
+///
+/// Fields set to `None` are left unchanged.
+///
+/// See [`Service::update_config`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ServiceConfigChange {
+    /// User-friendly service name
+    pub display_name: Option<OsString>,
+
+    /// The service type
+    pub service_type: Option<ServiceType>,
+
+    /// The service startup options
+    pub start_type: Option<ServiceStartType>,
+
+    /// The severity of the error, and action taken, if this service fails to start.
+    pub error_control: Option<ServiceErrorControl>,
+
+    /// The service binary along with its launch arguments.
+    ///
+    /// The [`ServiceBinary::Executable`] path is escaped according to
+    /// [`ServiceConfigChange::service_type`], or the current service type when it is not changed,
+    /// so that the driver paths are never quoted.
+    pub binary: Option<ServiceBinary>,
+
+    /// The load ordering group that the service belongs to.
+    /// Pass an empty string to remove the service from its group.
+    pub load_order_group: Option<OsString>,
+
+    /// Service dependencies
+    /// Pass an empty list to remove all dependencies.
+    pub dependencies: Option<Vec<ServiceDependency>>,
+
+    /// Account to use for running the service.
+    /// for example: NT Authority\System.
+    pub account_name: Option<OsString>,
+
+    /// Account password.
+    pub account_password: Option<SecretOsString>,
+}
+
+/// Escape executable path and arguments and combine them into a single command.
+fn launch_command(
+    service_type: ServiceType,
+    executable_path: &Path,
+    launch_arguments: &[OsString],
+) -> crate::Result<WideCString> {
+    let mut launch_command_buffer = WideString::new();
+    if service_type.intersects(ServiceType::KERNEL_DRIVER | ServiceType::FILE_SYSTEM_DRIVER) {
+        // drivers do not support launch arguments
+        if !launch_arguments.is_empty() {
+            return Err(Error::LaunchArgumentsNotSupported);
+        }
+
+        // also the path must not be quoted even if it contains spaces
+        let executable_path = WideCString::from_os_str(executable_path)
+            .map_err(|_| Error::ArgumentHasNulByte("executable path"))?;
+        launch_command_buffer.push(executable_path.to_ustring());
+    } else {
+        let executable_path = escape_wide(executable_path)
+            .map_err(|_| Error::ArgumentHasNulByte("executable path"))?;
+        launch_command_buffer.push(executable_path);
+
+        for (i, launch_argument) in launch_arguments.iter().enumerate() {
+            let wide = escape_wide(launch_argument)
+                .map_err(|_| Error::ArgumentArrayElementHasNulByte("launch argument", i))?;
+
+            launch_command_buffer.push_str(" ");
+            launch_command_buffer.push(wide);
+        }
+    }
+
+    // Safety: We are sure launch_command_buffer does not contain nulls
+    Ok(unsafe { WideCString::from_ustr_unchecked(launch_command_buffer) })
+}
+
+/// Join service dependencies into a double nul terminated string.
+///
+/// Returns `None` if there are no dependencies.
+fn joined_dependencies(dependencies: &[ServiceDependency]) -> crate::Result<Option<WideString>> {
+    let dependency_identifiers: Vec<OsString> = dependencies
+        .iter()
+        .map(|dependency| dependency.to_system_identifier())
+        .collect();
+    double_nul_terminated::from_slice(&dependency_identifiers)
+        .map_err(|_| Error::ArgumentHasNulByte("dependency"))
+}
+
+/// A struct that describes the service.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct ServiceConfig {
+    /// The service type
+    pub service_type: ServiceType,
+
+    /// The service startup options
+    pub start_type: ServiceStartType,
+
+    /// The severity of the error, and action taken, if this service fails to start.
+    pub error_control: ServiceErrorControl,
+
+    /// Path to the service binary, including launch arguments if there are any
+    pub executable_path: PathBuf,
+
+    /// The load ordering group that the service belongs to
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
+    pub load_order_group: Option<OsString>,
+
+    /// A unique tag value for this service in the group specified by the load_order_group
+    /// parameter.
+    ///
+    /// Zero if the service has no tag, which is the case for the services that are not drivers
+    /// or do not belong to a group.
+    pub tag_id: u32,
+
+    /// Service dependencies
+    pub dependencies: Vec<ServiceDependency>,
+
+    /// Account to use for running the service.
+    /// for example: NT Authority\System.
+    ///
+    /// This value can be `None` in certain cases, please refer to MSDN for more info:\
+    /// <https://docs.microsoft.com/en-us/windows/desktop/api/winsvc/ns-winsvc-_query_service_configw>
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
+    pub account_name: Option<OsString>,
 
     /// User-friendly service name
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::os_string"))]
     pub display_name: OsString,
 }
 
@@ -577,6 +1678,55 @@ impl ServiceConfig {
     }
 }
 
+/// The extended service configuration set through `ChangeServiceConfig2W`.
+///
+/// Fields are `None` when the system does not support the corresponding setting for the
+/// service.
+///
+/// See [`Service::query_extended_config`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct ExtendedServiceConfig {
+    /// The service description, `None` if the service has no description.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::option_os_string"))]
+    pub description: Option<OsString>,
+
+    /// Whether an auto-start service is started after other auto-start services.
+    pub delayed_auto_start: Option<bool>,
+
+    /// The service SID type.
+    pub sid_type: Option<ServiceSidType>,
+
+    /// The privileges required by the service.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_util::option_vec_os_string")
+    )]
+    pub required_privileges: Option<Vec<OsString>>,
+
+    /// The time the system waits for the service to stop on preshutdown.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_util::option_duration_millis")
+    )]
+    pub preshutdown_timeout: Option<Duration>,
+
+    /// The actions performed when the service fails.
+    pub failure_actions: Option<ServiceFailureActions>,
+
+    /// Whether the failure actions are run on non-crash failures.
+    pub failure_actions_on_non_crash_failures: Option<bool>,
+
+    /// The protection level of the service process.
+    ///
+    /// [`ServiceLaunchProtection::None`] on the systems that do not support protected services.
+    pub launch_protected: ServiceLaunchProtection,
+}
+
 /// Enum describing the event type of HardwareProfileChange
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u32)]
@@ -898,6 +2048,10 @@ impl PowerBroadcastSetting {
 }
 
 /// Enum describing the PowerEvent event
+///
+/// The service receives power events only when it accepts [`ServiceControlAccept::POWER_EVENT`].
+/// Power setting changes are delivered only for the power settings registered via
+/// `RegisterPowerSettingNotification` using the service status handle.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum PowerEventParam {
@@ -1010,7 +2164,9 @@ impl SessionChangeReason {
 /// Struct converted from RemoteDesktop::WTSSESSION_NOTIFICATION
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SessionNotification {
+    /// The size of the raw structure in bytes.
     pub size: u32,
+    /// The id of the session that has changed.
     pub session_id: u32,
 }
 
@@ -1024,9 +2180,15 @@ impl SessionNotification {
 }
 
 /// Struct describing the SessionChange event
+///
+/// The service receives session change events only when it accepts
+/// [`ServiceControlAccept::SESSION_CHANGE`] and registers the control handler via
+/// [`service_control_handler::register`](crate::service_control_handler::register).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SessionChangeParam {
+    /// The reason of the session change.
     pub reason: SessionChangeReason,
+    /// The notification carrying the id of the session that has changed.
     pub notification: SessionNotification,
 }
 
@@ -1050,6 +2212,189 @@ impl SessionChangeParam {
     }
 }
 
+/// Struct converted from Services::SERVICE_TIMECHANGE_INFO
+///
+/// Both times are expressed as the number of 100-nanosecond intervals since January 1, 1601
+/// (UTC), which is the `FILETIME` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeChangeParam {
+    /// The system time before the change.
+    pub old_time: i64,
+    /// The system time after the change.
+    pub new_time: i64,
+}
+
+impl TimeChangeParam {
+    /// Extract TimeChangeParam from `event_data`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `event_data` is null, the null pointer is reported as
+    /// [`ParseRawError::InvalidInteger`] holding zero.
+    ///
+    /// # Safety
+    ///
+    /// The `event_data` must be either null or a valid Services::SERVICE_TIMECHANGE_INFO pointer.
+    /// Otherwise, it is undefined behavior.
+    pub unsafe fn from_event(event_data: *mut c_void) -> Result<Self, ParseRawError> {
+        if event_data.is_null() {
+            return Err(ParseRawError::InvalidInteger(0));
+        }
+        let info = ptr::read_unaligned(event_data as *const Services::SERVICE_TIMECHANGE_INFO);
+        Ok(TimeChangeParam {
+            old_time: info.liOldTime,
+            new_time: info.liNewTime,
+        })
+    }
+}
+
+/// Enum describing the type of the DeviceEvent event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum DeviceEventType {
+    Arrival = WindowsAndMessaging::DBT_DEVICEARRIVAL,
+    QueryRemove = WindowsAndMessaging::DBT_DEVICEQUERYREMOVE,
+    QueryRemoveFailed = WindowsAndMessaging::DBT_DEVICEQUERYREMOVEFAILED,
+    RemovePending = WindowsAndMessaging::DBT_DEVICEREMOVEPENDING,
+    RemoveComplete = WindowsAndMessaging::DBT_DEVICEREMOVECOMPLETE,
+    TypeSpecific = WindowsAndMessaging::DBT_DEVICETYPESPECIFIC,
+    CustomEvent = WindowsAndMessaging::DBT_CUSTOMEVENT,
+}
+
+impl DeviceEventType {
+    pub fn from_raw(raw: u32) -> Result<DeviceEventType, ParseRawError> {
+        match raw {
+            x if x == DeviceEventType::Arrival.to_raw() => Ok(DeviceEventType::Arrival),
+            x if x == DeviceEventType::QueryRemove.to_raw() => Ok(DeviceEventType::QueryRemove),
+            x if x == DeviceEventType::QueryRemoveFailed.to_raw() => {
+                Ok(DeviceEventType::QueryRemoveFailed)
+            }
+            x if x == DeviceEventType::RemovePending.to_raw() => Ok(DeviceEventType::RemovePending),
+            x if x == DeviceEventType::RemoveComplete.to_raw() => {
+                Ok(DeviceEventType::RemoveComplete)
+            }
+            x if x == DeviceEventType::TypeSpecific.to_raw() => Ok(DeviceEventType::TypeSpecific),
+            x if x == DeviceEventType::CustomEvent.to_raw() => Ok(DeviceEventType::CustomEvent),
+            _ => Err(ParseRawError::InvalidInteger(raw)),
+        }
+    }
+
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+}
+
+/// Enum describing the type of the device that raised the DeviceEvent event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceType {
+    Oem,
+    Volume,
+    Port,
+    Net,
+    DeviceInterface,
+    Handle,
+    /// Any other device type, preserved as the raw `dbch_devicetype` value.
+    Other(u32),
+}
+
+impl DeviceType {
+    /// Parse the raw `dbch_devicetype` value.
+    ///
+    /// Unexpected values are preserved as [`DeviceType::Other`].
+    pub fn from_raw(raw: u32) -> DeviceType {
+        match raw {
+            WindowsAndMessaging::DBT_DEVTYP_OEM => DeviceType::Oem,
+            WindowsAndMessaging::DBT_DEVTYP_VOLUME => DeviceType::Volume,
+            WindowsAndMessaging::DBT_DEVTYP_PORT => DeviceType::Port,
+            WindowsAndMessaging::DBT_DEVTYP_NET => DeviceType::Net,
+            WindowsAndMessaging::DBT_DEVTYP_DEVICEINTERFACE => DeviceType::DeviceInterface,
+            WindowsAndMessaging::DBT_DEVTYP_HANDLE => DeviceType::Handle,
+            _ => DeviceType::Other(raw),
+        }
+    }
+
+    pub fn to_raw(&self) -> u32 {
+        match self {
+            DeviceType::Oem => WindowsAndMessaging::DBT_DEVTYP_OEM,
+            DeviceType::Volume => WindowsAndMessaging::DBT_DEVTYP_VOLUME,
+            DeviceType::Port => WindowsAndMessaging::DBT_DEVTYP_PORT,
+            DeviceType::Net => WindowsAndMessaging::DBT_DEVTYP_NET,
+            DeviceType::DeviceInterface => WindowsAndMessaging::DBT_DEVTYP_DEVICEINTERFACE,
+            DeviceType::Handle => WindowsAndMessaging::DBT_DEVTYP_HANDLE,
+            DeviceType::Other(raw) => *raw,
+        }
+    }
+}
+
+/// Struct describing the DeviceEvent event
+///
+/// The service receives device events only for the devices registered via
+/// `RegisterDeviceNotificationW` using the service status handle and the
+/// `DEVICE_NOTIFY_SERVICE_HANDLE` flag.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceEventParam {
+    /// The type of the device event.
+    pub event_type: DeviceEventType,
+    /// The type of the device that raised the event.
+    pub device_type: DeviceType,
+    /// The symbolic link name of the device interface.
+    /// Only present when the `device_type` is [`DeviceType::DeviceInterface`].
+    pub device_name: Option<OsString>,
+}
+
+impl DeviceEventParam {
+    /// Extract DeviceEventParam from `event_type` and `event_data`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `event_type` is not a valid device event type, or the
+    /// `event_data` is null, in which case the null pointer is reported as
+    /// [`ParseRawError::InvalidInteger`] holding zero. The device interface name is read within
+    /// the `dbch_size` of the structure, and an error is returned if the size is too small to
+    /// hold the name.
+    ///
+    /// # Safety
+    ///
+    /// The `event_data` must be either null or a valid WindowsAndMessaging::DEV_BROADCAST_HDR
+    /// pointer, followed by the rest of the structure corresponding to the device type.
+    /// Otherwise, it is undefined behavior.
+    pub unsafe fn from_event(
+        event_type: u32,
+        event_data: *mut c_void,
+    ) -> Result<Self, ParseRawError> {
+        let event_type = DeviceEventType::from_raw(event_type)?;
+        if event_data.is_null() {
+            return Err(ParseRawError::InvalidInteger(0));
+        }
+        let header = &*(event_data as *const WindowsAndMessaging::DEV_BROADCAST_HDR);
+        let device_type = DeviceType::from_raw(header.dbch_devicetype);
+
+        let device_name = match device_type {
+            DeviceType::DeviceInterface => {
+                let broadcast =
+                    event_data as *const WindowsAndMessaging::DEV_BROADCAST_DEVICEINTERFACE_W;
+                let name = ptr::addr_of!((*broadcast).dbcc_name) as *const u16;
+                // The name is not read past the size of the structure reported by the system.
+                let name_offset = name as usize - event_data as usize;
+                let size = usize::try_from(header.dbch_size).unwrap();
+                if size < name_offset {
+                    return Err(ParseRawError::InvalidInteger(header.dbch_size));
+                }
+                let name = std::slice::from_raw_parts(name, (size - name_offset) / 2);
+                let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+                Some(OsString::from_wide(&name[..len]))
+            }
+            _ => None,
+        };
+
+        Ok(DeviceEventParam {
+            event_type,
+            device_type,
+            device_name,
+        })
+    }
+}
+
 /// Struct describing a user-defined control code (**128** to **255**)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
@@ -1082,7 +2427,7 @@ impl UserEventCode {
 }
 
 /// Enum describing the service control operations.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum ServiceControl {
     Continue,
@@ -1096,10 +2441,13 @@ pub enum ServiceControl {
     Preshutdown,
     Shutdown,
     Stop,
+    DeviceEvent(DeviceEventParam),
     HardwareProfileChange(HardwareProfileChangeParam),
     PowerEvent(PowerEventParam),
     SessionChange(SessionChangeParam),
-    TimeChange,
+    TimeChange(TimeChangeParam),
+    /// Sent to the service that accepts [`ServiceControlAccept::TRIGGER_EVENT`] when the trigger
+    /// event it registered for occurs. The event carries no data.
     TriggerEvent,
     UserEvent(UserEventCode),
 }
@@ -1129,6 +2477,10 @@ impl ServiceControl {
             Services::SERVICE_CONTROL_PRESHUTDOWN => Ok(ServiceControl::Preshutdown),
             Services::SERVICE_CONTROL_SHUTDOWN => Ok(ServiceControl::Shutdown),
             Services::SERVICE_CONTROL_STOP => Ok(ServiceControl::Stop),
+            Services::SERVICE_CONTROL_DEVICEEVENT => {
+                DeviceEventParam::from_event(event_type, event_data)
+                    .map(ServiceControl::DeviceEvent)
+            }
             Services::SERVICE_CONTROL_HARDWAREPROFILECHANGE => {
                 HardwareProfileChangeParam::from_raw(event_type)
                     .map(ServiceControl::HardwareProfileChange)
@@ -1140,7 +2492,9 @@ impl ServiceControl {
                 SessionChangeParam::from_event(event_type, event_data)
                     .map(ServiceControl::SessionChange)
             }
-            Services::SERVICE_CONTROL_TIMECHANGE => Ok(ServiceControl::TimeChange),
+            Services::SERVICE_CONTROL_TIMECHANGE => {
+                TimeChangeParam::from_event(event_data).map(ServiceControl::TimeChange)
+            }
             Services::SERVICE_CONTROL_TRIGGEREVENT => Ok(ServiceControl::TriggerEvent),
             _ => UserEventCode::from_raw(raw).map(ServiceControl::UserEvent),
         }
@@ -1159,47 +2513,171 @@ impl ServiceControl {
             ServiceControl::Preshutdown => Services::SERVICE_CONTROL_PRESHUTDOWN,
             ServiceControl::Shutdown => Services::SERVICE_CONTROL_SHUTDOWN,
             ServiceControl::Stop => Services::SERVICE_CONTROL_STOP,
+            ServiceControl::DeviceEvent(_) => Services::SERVICE_CONTROL_DEVICEEVENT,
             ServiceControl::HardwareProfileChange(_) => {
                 Services::SERVICE_CONTROL_HARDWAREPROFILECHANGE
             }
             ServiceControl::PowerEvent(_) => Services::SERVICE_CONTROL_POWEREVENT,
             ServiceControl::SessionChange(_) => Services::SERVICE_CONTROL_SESSIONCHANGE,
-            ServiceControl::TimeChange => Services::SERVICE_CONTROL_TIMECHANGE,
+            ServiceControl::TimeChange(_) => Services::SERVICE_CONTROL_TIMECHANGE,
             ServiceControl::TriggerEvent => Services::SERVICE_CONTROL_TRIGGEREVENT,
             ServiceControl::UserEvent(event) => event.to_raw(),
         }
     }
 }
 
+impl ServiceControl {
+    /// Private helper returning the stable name of the control.
+    fn name(&self) -> &'static str {
+        match self {
+            ServiceControl::Continue => "continue",
+            ServiceControl::Interrogate => "interrogate",
+            ServiceControl::NetBindAdd => "net_bind_add",
+            ServiceControl::NetBindDisable => "net_bind_disable",
+            ServiceControl::NetBindEnable => "net_bind_enable",
+            ServiceControl::NetBindRemove => "net_bind_remove",
+            ServiceControl::ParamChange => "param_change",
+            ServiceControl::Pause => "pause",
+            ServiceControl::Preshutdown => "preshutdown",
+            ServiceControl::Shutdown => "shutdown",
+            ServiceControl::Stop => "stop",
+            ServiceControl::DeviceEvent(_) => "device_event",
+            ServiceControl::HardwareProfileChange(_) => "hardware_profile_change",
+            ServiceControl::PowerEvent(_) => "power_event",
+            ServiceControl::SessionChange(_) => "session_change",
+            ServiceControl::TimeChange(_) => "time_change",
+            ServiceControl::TriggerEvent => "trigger_event",
+            ServiceControl::UserEvent(_) => "user_event",
+        }
+    }
+}
+
+/// Formats the control as its stable name in snake case, such as `stop` or `power_event`, omitting
+/// the event parameters.
+impl std::fmt::Display for ServiceControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the name of a control that does not carry any event parameters, such as `stop`.
+impl std::str::FromStr for ServiceControl {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            ServiceControl::Continue,
+            ServiceControl::Interrogate,
+            ServiceControl::NetBindAdd,
+            ServiceControl::NetBindDisable,
+            ServiceControl::NetBindEnable,
+            ServiceControl::NetBindRemove,
+            ServiceControl::ParamChange,
+            ServiceControl::Pause,
+            ServiceControl::Preshutdown,
+            ServiceControl::Shutdown,
+            ServiceControl::Stop,
+            ServiceControl::TriggerEvent,
+        ]
+        .into_iter()
+        .find(|control| control.name() == s)
+        .ok_or_else(|| ParseNameError(s.to_owned()))
+    }
+}
+
 /// Service state returned as a part of [`ServiceStatus`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[repr(u32)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum ServiceState {
-    Stopped = Services::SERVICE_STOPPED,
-    StartPending = Services::SERVICE_START_PENDING,
-    StopPending = Services::SERVICE_STOP_PENDING,
-    Running = Services::SERVICE_RUNNING,
-    ContinuePending = Services::SERVICE_CONTINUE_PENDING,
-    PausePending = Services::SERVICE_PAUSE_PENDING,
-    Paused = Services::SERVICE_PAUSED,
+    Stopped,
+    StartPending,
+    StopPending,
+    Running,
+    ContinuePending,
+    PausePending,
+    Paused,
+
+    /// The state that is not known to this crate, e.g. reported by a misbehaving driver.
+    Unknown(u32),
 }
 
 impl ServiceState {
-    fn from_raw(raw: u32) -> Result<Self, ParseRawError> {
+    /// Parse the raw `dwCurrentState` value, such as `SERVICE_RUNNING`.
+    ///
+    /// Unexpected values are preserved as [`ServiceState::Unknown`].
+    pub fn from_raw(raw: u32) -> Self {
         match raw {
-            x if x == ServiceState::Stopped.to_raw() => Ok(ServiceState::Stopped),
-            x if x == ServiceState::StartPending.to_raw() => Ok(ServiceState::StartPending),
-            x if x == ServiceState::StopPending.to_raw() => Ok(ServiceState::StopPending),
-            x if x == ServiceState::Running.to_raw() => Ok(ServiceState::Running),
-            x if x == ServiceState::ContinuePending.to_raw() => Ok(ServiceState::ContinuePending),
-            x if x == ServiceState::PausePending.to_raw() => Ok(ServiceState::PausePending),
-            x if x == ServiceState::Paused.to_raw() => Ok(ServiceState::Paused),
-            _ => Err(ParseRawError::InvalidInteger(raw)),
+            Services::SERVICE_STOPPED => ServiceState::Stopped,
+            Services::SERVICE_START_PENDING => ServiceState::StartPending,
+            Services::SERVICE_STOP_PENDING => ServiceState::StopPending,
+            Services::SERVICE_RUNNING => ServiceState::Running,
+            Services::SERVICE_CONTINUE_PENDING => ServiceState::ContinuePending,
+            Services::SERVICE_PAUSE_PENDING => ServiceState::PausePending,
+            Services::SERVICE_PAUSED => ServiceState::Paused,
+            _ => ServiceState::Unknown(raw),
+        }
+    }
+
+    /// Returns the raw `dwCurrentState` value.
+    pub fn to_raw(self) -> u32 {
+        match self {
+            ServiceState::Stopped => Services::SERVICE_STOPPED,
+            ServiceState::StartPending => Services::SERVICE_START_PENDING,
+            ServiceState::StopPending => Services::SERVICE_STOP_PENDING,
+            ServiceState::Running => Services::SERVICE_RUNNING,
+            ServiceState::ContinuePending => Services::SERVICE_CONTINUE_PENDING,
+            ServiceState::PausePending => Services::SERVICE_PAUSE_PENDING,
+            ServiceState::Paused => Services::SERVICE_PAUSED,
+            ServiceState::Unknown(raw) => raw,
         }
     }
 
-    fn to_raw(self) -> u32 {
-        self as u32
+    /// Private helper returning the stable name of the known state.
+    fn name(self) -> Option<&'static str> {
+        match self {
+            ServiceState::Stopped => Some("stopped"),
+            ServiceState::StartPending => Some("start_pending"),
+            ServiceState::StopPending => Some("stop_pending"),
+            ServiceState::Running => Some("running"),
+            ServiceState::ContinuePending => Some("continue_pending"),
+            ServiceState::PausePending => Some("pause_pending"),
+            ServiceState::Paused => Some("paused"),
+            ServiceState::Unknown(_) => None,
+        }
+    }
+}
+
+/// Formats the state as its stable name in snake case, such as `stop_pending`, or `unknown(<raw>)`
+/// for the unknown states.
+impl std::fmt::Display for ServiceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "unknown({})", self.to_raw()),
+        }
+    }
+}
+
+impl std::str::FromStr for ServiceState {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            ServiceState::Stopped,
+            ServiceState::StartPending,
+            ServiceState::StopPending,
+            ServiceState::Running,
+            ServiceState::ContinuePending,
+            ServiceState::PausePending,
+            ServiceState::Paused,
+        ]
+        .into_iter()
+        .find(|state| state.name() == Some(s))
+        .ok_or_else(|| ParseNameError(s.to_owned()))
     }
 }
 
@@ -1218,8 +2696,16 @@ impl ServiceState {
 /// [`dwWin32ExitCode`]: Services::SERVICE_STATUS::dwWin32ExitCode
 /// [`dwServiceSpecificExitCode`]: Services::SERVICE_STATUS::dwServiceSpecificExitCode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum ServiceExitCode {
+    /// A Win32 error code, reported with `dwServiceSpecificExitCode` set to zero.
     Win32(u32),
+    /// A service-specific error code, reported with `dwWin32ExitCode` set to
+    /// [`ERROR_SERVICE_SPECIFIC_ERROR`].
     ServiceSpecific(u32),
 }
 
@@ -1269,7 +2755,17 @@ impl<'a> From<&'a Services::SERVICE_STATUS_PROCESS> for ServiceExitCode {
 
 bitflags::bitflags! {
     /// Flags describing accepted types of service control events.
+    ///
+    /// The service reports the accepted controls via [`ServiceStatus::controls_accepted`]. The
+    /// system never delivers the events for the controls that are not accepted. Conversely, every
+    /// accepted control has to be handled by the control handler, e.g. the service that accepts
+    /// [`ServiceControlAccept::STOP`] is expected to stop when it receives [`ServiceControl::Stop`].
     #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde_crate::Serialize, serde_crate::Deserialize),
+        serde(crate = "serde_crate", transparent)
+    )]
     pub struct ServiceControlAccept: u32 {
         /// The service is a network component that can accept changes in its binding without being
         /// stopped and restarted. This allows service to receive `ServiceControl::Netbind*`
@@ -1284,6 +2780,9 @@ bitflags::bitflags! {
 
         /// The service can perform preshutdown tasks.
         /// Mutually exclusive with shutdown.
+        ///
+        /// The system waits for the service to stop for up to the preshutdown timeout, which can
+        /// be configured via [`Service::set_preshutdown_timeout`].
         const PRESHUTDOWN = Services::SERVICE_ACCEPT_PRESHUTDOWN;
 
         /// The service is notified when system shutdown occurs.
@@ -1316,6 +2815,48 @@ bitflags::bitflags! {
     }
 }
 
+impl ServiceControlAccept {
+    /// Returns the flags that the service has to accept in order to receive the given control.
+    ///
+    /// The result is empty for the controls that are always delivered to the service, such as
+    /// [`ServiceControl::Interrogate`] or [`ServiceControl::UserEvent`].
+    pub fn required_for(control: &ServiceControl) -> Self {
+        match control {
+            ServiceControl::Continue | ServiceControl::Pause => Self::PAUSE_CONTINUE,
+            ServiceControl::NetBindAdd
+            | ServiceControl::NetBindDisable
+            | ServiceControl::NetBindEnable
+            | ServiceControl::NetBindRemove => Self::NETBIND_CHANGE,
+            ServiceControl::ParamChange => Self::PARAM_CHANGE,
+            ServiceControl::Preshutdown => Self::PRESHUTDOWN,
+            ServiceControl::Shutdown => Self::SHUTDOWN,
+            ServiceControl::Stop => Self::STOP,
+            ServiceControl::HardwareProfileChange(_) => Self::HARDWARE_PROFILE_CHANGE,
+            ServiceControl::PowerEvent(_) => Self::POWER_EVENT,
+            ServiceControl::SessionChange(_) => Self::SESSION_CHANGE,
+            ServiceControl::TimeChange(_) => Self::TIME_CHANGE,
+            ServiceControl::TriggerEvent => Self::TRIGGER_EVENT,
+            ServiceControl::Interrogate
+            | ServiceControl::DeviceEvent(_)
+            | ServiceControl::UserEvent(_) => Self::empty(),
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags describing the properties of a running service.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde_crate::Serialize, serde_crate::Deserialize),
+        serde(crate = "serde_crate", transparent)
+    )]
+    pub struct ServiceFlags: u32 {
+        /// The service runs in a system process that must always be running.
+        const RUNS_IN_SYSTEM_PROCESS = Services::SERVICE_RUNS_IN_SYSTEM_PROCESS;
+    }
+}
+
 /// Service status.
 ///
 /// This struct wraps the lower level [`SERVICE_STATUS`] providing a few convenience types to fill
@@ -1326,6 +2867,11 @@ bitflags::bitflags! {
 ///
 /// [`SERVICE_STATUS`]: Services::SERVICE_STATUS
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct ServiceStatus {
     /// Type of service.
     pub service_type: ServiceType,
@@ -1356,14 +2902,51 @@ pub struct ServiceStatus {
     ///
     /// Converting this to the FFI form will panic if the duration is too large to fit as
     /// milliseconds in a `u32`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_util::duration_millis"))]
     pub wait_hint: Duration,
 
     /// Process ID of the service
     /// This is only retrieved when querying the service status.
+    ///
+    /// `None` if the service process is not running. The id is also reported while the service
+    /// is starting or stopping.
     pub process_id: Option<u32>,
+
+    /// Properties of the service process
+    /// This is only retrieved when querying the service status.
+    pub service_flags: ServiceFlags,
 }
 
 impl ServiceStatus {
+    /// Returns `true` if the service accepts the given control, according to
+    /// [`ServiceStatus::controls_accepted`].
+    ///
+    /// Sending a control that is not accepted, e.g. via [`Service::stop`], fails with
+    /// [`Error::InvalidServiceControl`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceAccess, ServiceControl};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service(
+    ///     "my_service",
+    ///     ServiceAccess::QUERY_STATUS | ServiceAccess::STOP,
+    /// )?;
+    /// if my_service.query_status()?.accepts(&ServiceControl::Stop) {
+    ///     my_service.stop()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn accepts(&self, control: &ServiceControl) -> bool {
+        self.controls_accepted
+            .contains(ServiceControlAccept::required_for(control))
+    }
+
     pub(crate) fn to_raw(&self) -> Services::SERVICE_STATUS {
         let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS>() };
         raw_status.dwServiceType = self.service_type.bits();
@@ -1380,626 +2963,3381 @@ impl ServiceStatus {
         raw_status
     }
 
-    /// Tries to parse a `SERVICE_STATUS` into a Rust [`ServiceStatus`].
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the `dwCurrentState` field does not represent a valid [`ServiceState`].
-    fn from_raw(raw: Services::SERVICE_STATUS) -> Result<Self, ParseRawError> {
-        Ok(ServiceStatus {
+    /// Parse a `SERVICE_STATUS` into a Rust [`ServiceStatus`].
+    fn from_raw(raw: Services::SERVICE_STATUS) -> Self {
+        ServiceStatus {
             service_type: ServiceType::from_bits_truncate(raw.dwServiceType),
-            current_state: ServiceState::from_raw(raw.dwCurrentState)?,
+            current_state: ServiceState::from_raw(raw.dwCurrentState),
             controls_accepted: ServiceControlAccept::from_bits_truncate(raw.dwControlsAccepted),
             exit_code: ServiceExitCode::from(&raw),
             checkpoint: raw.dwCheckPoint,
             wait_hint: Duration::from_millis(raw.dwWaitHint as u64),
             process_id: None,
-        })
+            service_flags: ServiceFlags::empty(),
+        }
     }
 
-    /// Tries to parse a `SERVICE_STATUS_PROCESS` into a Rust [`ServiceStatus`].
-    ///
-    /// # Errors
+    /// Parse a `SERVICE_STATUS_PROCESS` into a Rust [`ServiceStatus`].
     ///
-    /// Returns an error if the `dwCurrentState` field does not represent a valid [`ServiceState`].
-    fn from_raw_ex(raw: Services::SERVICE_STATUS_PROCESS) -> Result<Self, ParseRawError> {
-        let current_state = ServiceState::from_raw(raw.dwCurrentState)?;
-        let process_id = match current_state {
-            ServiceState::Running => Some(raw.dwProcessId),
-            _ => None,
+    /// All fields are parsed regardless of the service state.
+    pub(crate) fn from_raw_ex(raw: Services::SERVICE_STATUS_PROCESS) -> Self {
+        let process_id = if raw.dwProcessId == 0 {
+            None
+        } else {
+            Some(raw.dwProcessId)
         };
-        Ok(ServiceStatus {
+        ServiceStatus {
             service_type: ServiceType::from_bits_truncate(raw.dwServiceType),
-            current_state,
+            current_state: ServiceState::from_raw(raw.dwCurrentState),
             controls_accepted: ServiceControlAccept::from_bits_truncate(raw.dwControlsAccepted),
             exit_code: ServiceExitCode::from(&raw),
             checkpoint: raw.dwCheckPoint,
             wait_hint: Duration::from_millis(raw.dwWaitHint as u64),
             process_id,
-        })
+            service_flags: ServiceFlags::from_bits_truncate(raw.dwServiceFlags),
+        }
     }
 }
 
-/// This controls how the service SID is added to the service process token.
-/// <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_sid_info>
+/// Enum describing the state of services to enumerate.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u32)]
-pub enum ServiceSidType {
-    None = 0,
-    Restricted = 3,
-    Unrestricted = 1,
+pub enum ServiceStateFilter {
+    /// Services that are in the start pending, stop pending, running, continue pending,
+    /// pause pending or paused state.
+    Active = Services::SERVICE_ACTIVE,
+    /// Services that are in the stopped state.
+    Inactive = Services::SERVICE_INACTIVE,
+    /// Services in any state.
+    All = Services::SERVICE_STATE_ALL,
 }
 
-/// A struct that represents a system service.
+impl ServiceStateFilter {
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+}
+
+/// A struct that describes a service returned from the enumeration of services.
 ///
-/// The instances of the [`Service`] can be obtained via [`ServiceManager`].
+/// See [`ServiceManager::enumerate_services`] and [`Service::enumerate_dependent_services`].
 ///
-/// [`ServiceManager`]: super::service_manager::ServiceManager
-pub struct Service {
-    service_handle: ScHandle,
-}
+/// [`ServiceManager::enumerate_services`]: super::service_manager::ServiceManager::enumerate_services
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServiceEntry {
+    /// Service name
+    pub name: OsString,
 
-impl Service {
-    pub(crate) fn new(service_handle: ScHandle) -> Self {
-        Service { service_handle }
-    }
+    /// User-friendly service name
+    pub display_name: OsString,
 
-    /// Provides access to the underlying system service handle
-    pub fn raw_handle(&self) -> Security::SC_HANDLE {
-        self.service_handle.raw_handle()
-    }
+    /// Current status of the service
+    pub status: ServiceStatus,
+}
 
-    /// Start the service.
-    ///
-    /// # Example
+impl ServiceEntry {
+    /// Parse an `ENUM_SERVICE_STATUSW` into Rust [`ServiceEntry`].
     ///
-    /// ```rust,no_run
-    /// use std::ffi::OsStr;
-    /// use windows_service::service::ServiceAccess;
-    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    /// # Safety
     ///
-    /// # fn main() -> windows_service::Result<()> {
-    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
-    /// let my_service = manager.open_service("my_service", ServiceAccess::START)?;
-    /// my_service.start(&[OsStr::new("Started from Rust!")])?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn start<S: AsRef<OsStr>>(&self, service_arguments: &[S]) -> crate::Result<()> {
-        let wide_service_arguments = service_arguments
-            .iter()
-            .map(|s| {
-                WideCString::from_os_str(s).map_err(|_| Error::ArgumentHasNulByte("start argument"))
-            })
-            .collect::<crate::Result<Vec<WideCString>>>()?;
-
-        let raw_service_arguments: Vec<*const u16> = wide_service_arguments
-            .iter()
-            .map(|s| s.as_ptr() as _)
-            .collect();
-
-        let success = unsafe {
-            Services::StartServiceW(
-                self.service_handle.raw_handle(),
-                raw_service_arguments.len() as u32,
-                raw_service_arguments.as_ptr(),
-            )
-        };
-
-        if success == 0 {
-            Err(Error::Winapi(io::Error::last_os_error()))
-        } else {
-            Ok(())
+    /// `lpServiceName` and `lpDisplayName` must be proper null terminated wide C strings.
+    pub unsafe fn from_raw(raw: Services::ENUM_SERVICE_STATUSW) -> ServiceEntry {
+        ServiceEntry {
+            name: WideCStr::from_ptr_str(raw.lpServiceName).to_os_string(),
+            display_name: WideCStr::from_ptr_str(raw.lpDisplayName).to_os_string(),
+            status: ServiceStatus::from_raw(raw.ServiceStatus),
         }
     }
 
-    /// Stop the service.
-    pub fn stop(&self) -> crate::Result<ServiceStatus> {
-        self.send_control_command(ServiceControl::Stop)
-    }
-
-    /// Pause the service.
-    ///
-    /// # Example
+    /// Parse an `ENUM_SERVICE_STATUS_PROCESSW` into Rust [`ServiceEntry`].
     ///
-    /// ```rust,no_run
-    /// use windows_service::service::ServiceAccess;
-    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    /// # Safety
     ///
-    /// # fn main() -> windows_service::Result<()> {
-    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
-    /// let my_service = manager.open_service("my_service", ServiceAccess::PAUSE_CONTINUE)?;
-    /// my_service.pause()?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn pause(&self) -> crate::Result<ServiceStatus> {
-        self.send_control_command(ServiceControl::Pause)
+    /// `lpServiceName` and `lpDisplayName` must be proper null terminated wide C strings.
+    pub unsafe fn from_raw_ex(raw: Services::ENUM_SERVICE_STATUS_PROCESSW) -> ServiceEntry {
+        ServiceEntry {
+            name: WideCStr::from_ptr_str(raw.lpServiceName).to_os_string(),
+            display_name: WideCStr::from_ptr_str(raw.lpDisplayName).to_os_string(),
+            status: ServiceStatus::from_raw_ex(raw.ServiceStatusProcess),
+        }
     }
+}
 
-    /// Resume the paused service.
-    pub fn resume(&self) -> crate::Result<ServiceStatus> {
-        self.send_control_command(ServiceControl::Continue)
-    }
+/// This controls how the service SID is added to the service process token.
+/// <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_sid_info>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[repr(u32)]
+pub enum ServiceSidType {
+    None = Services::SERVICE_SID_TYPE_NONE,
+    Restricted = 3, // SERVICE_SID_TYPE_RESTRICTED
+    Unrestricted = Services::SERVICE_SID_TYPE_UNRESTRICTED,
+}
 
-    /// Send user-defined control code.
-    pub fn notify(&self, code: UserEventCode) -> crate::Result<ServiceStatus> {
-        self.send_control_command(ServiceControl::UserEvent(code))
+impl ServiceSidType {
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
     }
 
-    /// Get the service status from the system.
-    pub fn query_status(&self) -> crate::Result<ServiceStatus> {
-        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS_PROCESS>() };
-        let mut bytes_needed: u32 = 0;
-        let success = unsafe {
-            Services::QueryServiceStatusEx(
-                self.service_handle.raw_handle(),
-                Services::SC_STATUS_PROCESS_INFO,
-                &mut raw_status as *mut _ as _,
-                std::mem::size_of::<Services::SERVICE_STATUS_PROCESS>() as u32,
-                &mut bytes_needed,
-            )
-        };
-        if success == 0 {
-            Err(Error::Winapi(io::Error::last_os_error()))
-        } else {
-            ServiceStatus::from_raw_ex(raw_status)
-                .map_err(|e| Error::ParseValue("service status", e))
+    pub fn from_raw(raw: u32) -> Result<ServiceSidType, ParseRawError> {
+        match raw {
+            x if x == ServiceSidType::None.to_raw() => Ok(ServiceSidType::None),
+            x if x == ServiceSidType::Restricted.to_raw() => Ok(ServiceSidType::Restricted),
+            x if x == ServiceSidType::Unrestricted.to_raw() => Ok(ServiceSidType::Unrestricted),
+            _ => Err(ParseRawError::InvalidInteger(raw)),
         }
     }
+}
 
-    /// Mark the service for deletion from the service control manager database.
-    ///
-    /// The database entry is not removed until all open handles to the service have been closed
-    /// and the service is stopped. If the service is not or cannot be stopped, the database entry
-    /// is removed when the system is restarted. This function will return an error if the service
-    /// has already been marked for deletion.
-    pub fn delete(&self) -> crate::Result<()> {
-        let success = unsafe { Services::DeleteService(self.service_handle.raw_handle()) };
-        if success == 0 {
-            Err(Error::Winapi(io::Error::last_os_error()))
-        } else {
-            Ok(())
+/// Enum describing the protection level of the service process.
+///
+/// See <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-service_launch_protected_info>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[repr(u32)]
+pub enum ServiceLaunchProtection {
+    /// The service process is not protected.
+    None = Services::SERVICE_LAUNCH_PROTECTED_NONE,
+    /// The service process runs as a protected process.
+    Windows = Services::SERVICE_LAUNCH_PROTECTED_WINDOWS,
+    /// The service process runs as a protected process light.
+    WindowsLight = Services::SERVICE_LAUNCH_PROTECTED_WINDOWS_LIGHT,
+    /// The service process runs as an antimalware protected process light.
+    AntimalwareLight = Services::SERVICE_LAUNCH_PROTECTED_ANTIMALWARE_LIGHT,
+}
+
+impl ServiceLaunchProtection {
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn from_raw(raw: u32) -> Result<ServiceLaunchProtection, ParseRawError> {
+        match raw {
+            x if x == ServiceLaunchProtection::None.to_raw() => Ok(ServiceLaunchProtection::None),
+            x if x == ServiceLaunchProtection::Windows.to_raw() => {
+                Ok(ServiceLaunchProtection::Windows)
+            }
+            x if x == ServiceLaunchProtection::WindowsLight.to_raw() => {
+                Ok(ServiceLaunchProtection::WindowsLight)
+            }
+            x if x == ServiceLaunchProtection::AntimalwareLight.to_raw() => {
+                Ok(ServiceLaunchProtection::AntimalwareLight)
+            }
+            _ => Err(ParseRawError::InvalidInteger(raw)),
         }
     }
+}
 
-    /// Get the service config from the system.
-    pub fn query_config(&self) -> crate::Result<ServiceConfig> {
-        // As per docs, the maximum size of data buffer used by QueryServiceConfigW is 8K
-        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
-        let mut bytes_written: u32 = 0;
+/// Enum describing whether the service stop was planned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum StopReasonFlag {
+    /// The reason code is defined by the user. Requires custom major and minor reason codes.
+    Custom = Services::SERVICE_STOP_REASON_FLAG_CUSTOM,
+    /// The service stop was planned.
+    Planned = Services::SERVICE_STOP_REASON_FLAG_PLANNED,
+    /// The service stop was not planned.
+    Unplanned = Services::SERVICE_STOP_REASON_FLAG_UNPLANNED,
+}
 
-        let success = unsafe {
-            Services::QueryServiceConfigW(
-                self.service_handle.raw_handle(),
-                data.as_mut_ptr() as _,
-                data.len() as u32,
-                &mut bytes_written,
-            )
-        };
+impl StopReasonFlag {
+    pub fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+}
 
-        if success == 0 {
-            Err(Error::Winapi(io::Error::last_os_error()))
-        } else {
-            unsafe {
-                let raw_config = data.as_ptr() as *const Services::QUERY_SERVICE_CONFIGW;
-                ServiceConfig::from_raw(*raw_config)
+/// Enum describing the major reason for stopping the service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReasonMajor {
+    Other,
+    Hardware,
+    OperatingSystem,
+    Software,
+    Application,
+    None,
+    /// The user-defined major reason code, in range from `0x40` to `0xff`.
+    Custom(u8),
+}
+
+impl StopReasonMajor {
+    pub fn to_raw(&self) -> u32 {
+        match self {
+            StopReasonMajor::Other => Services::SERVICE_STOP_REASON_MAJOR_OTHER,
+            StopReasonMajor::Hardware => Services::SERVICE_STOP_REASON_MAJOR_HARDWARE,
+            StopReasonMajor::OperatingSystem => Services::SERVICE_STOP_REASON_MAJOR_OPERATINGSYSTEM,
+            StopReasonMajor::Software => Services::SERVICE_STOP_REASON_MAJOR_SOFTWARE,
+            StopReasonMajor::Application => Services::SERVICE_STOP_REASON_MAJOR_APPLICATION,
+            StopReasonMajor::None => Services::SERVICE_STOP_REASON_MAJOR_NONE,
+            StopReasonMajor::Custom(code) => u32::from(*code) << 16,
+        }
+    }
+}
+
+/// Enum describing the minor reason for stopping the service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReasonMinor {
+    Other,
+    Maintenance,
+    Installation,
+    Upgrade,
+    Reconfig,
+    Hung,
+    Unstable,
+    Disk,
+    NetworkCard,
+    Environment,
+    HardwareDriver,
+    OtherDriver,
+    ServicePack,
+    SoftwareUpdate,
+    SecurityFix,
+    Security,
+    NetworkConnectivity,
+    Wmi,
+    ServicePackUninstall,
+    SoftwareUpdateUninstall,
+    SecurityFixUninstall,
+    Mmc,
+    None,
+    MemoryLimit,
+    /// The user-defined minor reason code, in range from `0x100` to `0xffff`.
+    Custom(u16),
+}
+
+impl StopReasonMinor {
+    pub fn to_raw(&self) -> u32 {
+        match self {
+            StopReasonMinor::Other => Services::SERVICE_STOP_REASON_MINOR_OTHER,
+            StopReasonMinor::Maintenance => Services::SERVICE_STOP_REASON_MINOR_MAINTENANCE,
+            StopReasonMinor::Installation => Services::SERVICE_STOP_REASON_MINOR_INSTALLATION,
+            StopReasonMinor::Upgrade => Services::SERVICE_STOP_REASON_MINOR_UPGRADE,
+            StopReasonMinor::Reconfig => Services::SERVICE_STOP_REASON_MINOR_RECONFIG,
+            StopReasonMinor::Hung => Services::SERVICE_STOP_REASON_MINOR_HUNG,
+            StopReasonMinor::Unstable => Services::SERVICE_STOP_REASON_MINOR_UNSTABLE,
+            StopReasonMinor::Disk => Services::SERVICE_STOP_REASON_MINOR_DISK,
+            StopReasonMinor::NetworkCard => Services::SERVICE_STOP_REASON_MINOR_NETWORKCARD,
+            StopReasonMinor::Environment => Services::SERVICE_STOP_REASON_MINOR_ENVIRONMENT,
+            StopReasonMinor::HardwareDriver => Services::SERVICE_STOP_REASON_MINOR_HARDWARE_DRIVER,
+            StopReasonMinor::OtherDriver => Services::SERVICE_STOP_REASON_MINOR_OTHERDRIVER,
+            StopReasonMinor::ServicePack => Services::SERVICE_STOP_REASON_MINOR_SERVICEPACK,
+            StopReasonMinor::SoftwareUpdate => Services::SERVICE_STOP_REASON_MINOR_SOFTWARE_UPDATE,
+            StopReasonMinor::SecurityFix => Services::SERVICE_STOP_REASON_MINOR_SECURITYFIX,
+            StopReasonMinor::Security => Services::SERVICE_STOP_REASON_MINOR_SECURITY,
+            StopReasonMinor::NetworkConnectivity => {
+                Services::SERVICE_STOP_REASON_MINOR_NETWORK_CONNECTIVITY
+            }
+            StopReasonMinor::Wmi => Services::SERVICE_STOP_REASON_MINOR_WMI,
+            StopReasonMinor::ServicePackUninstall => {
+                Services::SERVICE_STOP_REASON_MINOR_SERVICEPACK_UNINSTALL
+            }
+            StopReasonMinor::SoftwareUpdateUninstall => {
+                Services::SERVICE_STOP_REASON_MINOR_SOFTWARE_UPDATE_UNINSTALL
+            }
+            StopReasonMinor::SecurityFixUninstall => {
+                Services::SERVICE_STOP_REASON_MINOR_SECURITYFIX_UNINSTALL
             }
+            StopReasonMinor::Mmc => Services::SERVICE_STOP_REASON_MINOR_MMC,
+            StopReasonMinor::None => Services::SERVICE_STOP_REASON_MINOR_NONE,
+            StopReasonMinor::MemoryLimit => Services::SERVICE_STOP_REASON_MINOR_MEMOTYLIMIT,
+            StopReasonMinor::Custom(code) => u32::from(*code),
         }
     }
+}
 
-    /// Update the service config.
-    /// Caveat: You cannot reset the account name/password by passing NULL.
-    ///
-    /// This implementation does not currently expose the full flexibility of the
-    /// `ChangeServiceConfigW` API. When calling the API it's possible to pass NULL in place of
-    /// any of the string arguments to indicate that they should not be updated.
-    ///
-    /// If we wanted to support this we wouldn't be able to reuse the `ServiceInfo` struct.
-    pub fn change_config(&self, service_info: &ServiceInfo) -> crate::Result<()> {
-        let raw_info = RawServiceInfo::new(service_info)?;
-        let success = unsafe {
-            Services::ChangeServiceConfigW(
-                self.service_handle.raw_handle(),
-                raw_info.service_type,
-                raw_info.start_type,
-                raw_info.error_control,
-                raw_info.launch_command.as_ptr(),
-                ptr::null(),     // load ordering group
-                ptr::null_mut(), // tag id within the load ordering group
-                raw_info
-                    .dependencies
-                    .as_ref()
-                    .map_or(ptr::null(), |s| s.as_ptr()),
-                raw_info
-                    .account_name
-                    .as_ref()
-                    .map_or(ptr::null(), |s| s.as_ptr()),
-                raw_info
-                    .account_password
-                    .as_ref()
-                    .map_or(ptr::null(), |s| s.as_ptr()),
-                raw_info.display_name.as_ptr(),
-            )
+/// A struct that describes the reason for stopping the service, which is recorded in the system
+/// event log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StopReason {
+    /// Whether the service stop was planned.
+    pub flag: StopReasonFlag,
+
+    /// The major reason for stopping the service.
+    pub major: StopReasonMajor,
+
+    /// The minor reason for stopping the service.
+    pub minor: StopReasonMinor,
+}
+
+impl StopReason {
+    pub fn to_raw(&self) -> u32 {
+        self.flag.to_raw() | self.major.to_raw() | self.minor.to_raw()
+    }
+
+    /// Private helper to check that the custom reason codes are in range and are only combined
+    /// with [`StopReasonFlag::Custom`].
+    fn validate(&self) -> crate::Result<()> {
+        let custom_major = match self.major {
+            StopReasonMajor::Custom(code) if code < 0x40 => {
+                return Err(Error::InvalidServiceInfo(
+                    "custom major stop reason must be in range from 0x40 to 0xff",
+                ))
+            }
+            StopReasonMajor::Custom(_) => true,
+            _ => false,
+        };
+        let custom_minor = match self.minor {
+            StopReasonMinor::Custom(code) if code < 0x100 => {
+                return Err(Error::InvalidServiceInfo(
+                    "custom minor stop reason must be in range from 0x100 to 0xffff",
+                ))
+            }
+            StopReasonMinor::Custom(_) => true,
+            _ => false,
         };
+        let custom_flag = self.flag == StopReasonFlag::Custom;
 
-        if success == 0 {
-            Err(Error::Winapi(io::Error::last_os_error()))
+        if custom_flag != custom_major || custom_flag != custom_minor {
+            Err(Error::InvalidServiceInfo(
+                "custom stop reason flag requires custom major and minor reasons",
+            ))
         } else {
             Ok(())
         }
     }
+}
 
-    /// Configure failure actions to run when the service terminates before reporting the
-    /// [`ServiceState::Stopped`] back to the system or if it exits with non-zero
-    /// [`ServiceExitCode`].
-    ///
-    /// Please refer to MSDN for more info:\
-    /// <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-_service_failure_actions_flag>
-    pub fn set_failure_actions_on_non_crash_failures(&self, enabled: bool) -> crate::Result<()> {
-        let mut raw_failure_actions_flag =
-            unsafe { mem::zeroed::<Services::SERVICE_FAILURE_ACTIONS_FLAG>() };
-
-        raw_failure_actions_flag.fFailureActionsOnNonCrashFailures = if enabled { 1 } else { 0 };
+/// A struct that represents a system service.
+///
+/// The instances of the [`Service`] can be obtained via [`ServiceManager`].
+///
+/// [`Service`] is `Send` and `Sync`, so it can be shared between threads, e.g. to wait for the
+/// service to stop on one thread while querying its config on another one.
+///
+/// [`ServiceManager`]: super::service_manager::ServiceManager
+pub struct Service {
+    service_handle: ScHandle,
+    tag_id: Option<u32>,
+}
 
-        unsafe {
-            self.change_config2(
-                Services::SERVICE_CONFIG_FAILURE_ACTIONS_FLAG,
-                &mut raw_failure_actions_flag,
-            )
-            .map_err(Error::Winapi)
+impl Service {
+    pub(crate) fn new(service_handle: ScHandle) -> Self {
+        Service {
+            service_handle,
+            tag_id: None,
         }
     }
 
-    /// Query the system for the boolean indication that the service is configured to run failure
-    /// actions on non-crash failures.
-    pub fn get_failure_actions_on_non_crash_failures(&self) -> crate::Result<bool> {
-        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+    pub(crate) fn with_tag_id(service_handle: ScHandle, tag_id: Option<u32>) -> Self {
+        Service {
+            service_handle,
+            tag_id,
+        }
+    }
 
-        let raw_failure_actions_flag: Services::SERVICE_FAILURE_ACTIONS_FLAG = unsafe {
-            self.query_config2(Services::SERVICE_CONFIG_FAILURE_ACTIONS_FLAG, &mut data)
-                .map_err(Error::Winapi)?
-        };
-        Ok(raw_failure_actions_flag.fFailureActionsOnNonCrashFailures != 0)
+    /// Create a [`Service`] from a service handle obtained elsewhere, such as via
+    /// `OpenServiceW` or from another component.
+    ///
+    /// The ownership of the handle is transferred to the returned [`Service`], which closes it
+    /// when dropped. The caller must not close the handle afterwards.
+    ///
+    /// # Safety
+    ///
+    /// The `handle` must be a valid service handle, which is not owned by anything else.
+    pub unsafe fn from_raw_handle(handle: Security::SC_HANDLE) -> Self {
+        Service::new(ScHandle::new(handle))
     }
 
-    /// Query the system for the service's SID type information.
+    /// Create a [`Service`] borrowing a service handle owned elsewhere.
     ///
-    /// The service must be open with the [`ServiceAccess::QUERY_CONFIG`]
-    /// access permission prior to calling this method.
-    pub fn get_config_service_sid_info(&self) -> crate::Result<ServiceSidType> {
-        let mut data = vec![0u8; u32::BITS as usize / 8];
+    /// Unlike [`Service::from_raw_handle`], the returned [`Service`] never closes the handle, so
+    /// it remains owned by the caller.
+    ///
+    /// # Safety
+    ///
+    /// The `handle` must be a valid service handle, which remains open for as long as the
+    /// returned [`Service`] is used.
+    pub unsafe fn from_borrowed_raw_handle(handle: Security::SC_HANDLE) -> Self {
+        Service::new(ScHandle::borrowed(handle))
+    }
 
-        // SAFETY: The structure we get back is `SERVICE_SID_INFO`. It has a
-        // single member that specifies the new SID type as a `u32`, and as
-        // such, we can get away with not explicitly creating a structure and
-        // instead re-using `ServiceSidType` that is `repr(u32)`.
-        unsafe { self.query_config2(Services::SERVICE_CONFIG_SERVICE_SID_INFO, &mut data) }
-            .map_err(Error::Winapi)
+    /// Returns `true` if the service handle is closed when [`Service`] is dropped.
+    pub(crate) fn owns_handle(&self) -> bool {
+        self.service_handle.is_owned()
     }
 
-    /// Require the system to set the service's SID type information to the
-    /// provided value.
+    /// Provides access to the underlying system service handle
     ///
-    /// The service must be open with the [`ServiceAccess::CHANGE_CONFIG`]
-    /// access permission prior to calling this method.
-    pub fn set_config_service_sid_info(
-        &self,
-        mut service_sid_type: ServiceSidType,
-    ) -> crate::Result<()> {
-        // SAFETY: The structure we need to pass in is `SERVICE_SID_INFO`.
-        // It has a single member that specifies the new SID type, and as such,
-        // we can get away with not explicitly creating a structure in Rust.
-        unsafe {
-            self.change_config2(
-                Services::SERVICE_CONFIG_SERVICE_SID_INFO,
-                &mut service_sid_type,
-            )
-            .map_err(Error::Winapi)
-        }
+    /// Allows calling the Windows APIs not covered by this crate. The handle remains owned by
+    /// [`Service`] and is closed when it is dropped, so it must not be closed by the caller nor
+    /// used after [`Service`] is dropped, unless [`Service`] was created via
+    /// [`Service::from_borrowed_raw_handle`].
+    pub fn raw_handle(&self) -> Security::SC_HANDLE {
+        self.service_handle.raw_handle()
     }
 
-    /// Query the configured failure actions for the service.
-    pub fn get_failure_actions(&self) -> crate::Result<ServiceFailureActions> {
-        unsafe {
-            let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+    /// The tag assigned by the system when the driver service was created in a load ordering
+    /// group.
+    ///
+    /// This is only available on the [`Service`] returned by [`ServiceManager::create_service`],
+    /// use [`Service::query_config`] to obtain the tag of an existing service.
+    ///
+    /// Note that this is the tag used for ordering the start of the drivers within a group, which
+    /// is unrelated to the service tag that the system assigns to the threads of the services
+    /// sharing a process. The latter is not exposed by the public Windows APIs.
+    ///
+    /// [`ServiceManager::create_service`]: super::service_manager::ServiceManager::create_service
+    pub fn tag_id(&self) -> Option<u32> {
+        self.tag_id
+    }
 
-            let raw_failure_actions: Services::SERVICE_FAILURE_ACTIONSW = self
-                .query_config2(Services::SERVICE_CONFIG_FAILURE_ACTIONS, &mut data)
-                .map_err(Error::Winapi)?;
+    /// Start the service.
+    ///
+    /// Pass an empty collection to start the service without arguments.
+    ///
+    /// Returns [`Error::ServiceAlreadyRunning`] if an instance of the service is already running.
+    ///
+    /// Required permission: [`ServiceAccess::START`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::ffi::OsStr;
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::START)?;
+    /// my_service.start(&[OsStr::new("Started from Rust!")])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start<I, S>(&self, service_arguments: I) -> crate::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let wide_service_arguments = service_arguments
+            .into_iter()
+            .map(|s| {
+                WideCString::from_os_str(s).map_err(|_| Error::ArgumentHasNulByte("start argument"))
+            })
+            .collect::<crate::Result<Vec<WideCString>>>()?;
 
-            ServiceFailureActions::from_raw(raw_failure_actions)
+        let raw_service_arguments: Vec<*const u16> = wide_service_arguments
+            .iter()
+            .map(|s| s.as_ptr() as _)
+            .collect();
+
+        let success = unsafe {
+            Services::StartServiceW(
+                self.service_handle.raw_handle(),
+                raw_service_arguments.len() as u32,
+                if raw_service_arguments.is_empty() {
+                    ptr::null()
+                } else {
+                    raw_service_arguments.as_ptr()
+                },
+            )
+        };
+
+        if success == 0 {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() == Some(ERROR_SERVICE_ALREADY_RUNNING as i32) {
+                Err(Error::ServiceAlreadyRunning)
+            } else {
+                Err(Error::Winapi(error))
+            }
+        } else {
+            Ok(())
         }
     }
 
-    /// Update failure actions.
+    /// Start the service and wait until it reaches the [`ServiceState::Running`] state.
     ///
-    /// Pass `None` for optional fields to keep the corresponding fields unchanged, or pass an empty
-    /// value to reset them.
+    /// The service status is polled the same way as in [`Service::stop_and_wait`].
     ///
+    /// Returns [`Error::ServiceFailedToStart`] with the exit code reported by the service if it
+    /// stopped instead of reaching the running state, or [`Error::Timeout`] if the service is still
+    /// starting when the `timeout` elapses.
+    ///
+    /// Required permissions: [`ServiceAccess::START`], [`ServiceAccess::QUERY_STATUS`].
     ///
     /// # Example
     ///
     /// ```rust,no_run
-    /// use std::ffi::OsString;
+    /// use std::ffi::OsStr;
     /// use std::time::Duration;
-    /// use windows_service::service::{
-    ///     ServiceAccess, ServiceAction, ServiceActionType, ServiceFailureActions,
-    ///     ServiceFailureResetPeriod,
-    /// };
+    /// use windows_service::service::ServiceAccess;
     /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
     ///
     /// # fn main() -> windows_service::Result<()> {
     /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
     /// let my_service = manager.open_service(
     ///     "my_service",
-    ///     ServiceAccess::START | ServiceAccess::CHANGE_CONFIG,
+    ///     ServiceAccess::START | ServiceAccess::QUERY_STATUS,
     /// )?;
+    /// my_service.start_and_wait(&[] as &[&OsStr], Duration::from_secs(30))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_and_wait<I, S>(
+        &self,
+        service_arguments: I,
+        timeout: Duration,
+    ) -> crate::Result<ServiceStatus>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.start_and_wait_inner(service_arguments, timeout, None)
+    }
+
+    /// Start the service and wait until it reaches the [`ServiceState::Running`] state, unless
+    /// the wait is cancelled.
     ///
-    /// let actions = vec![
-    ///     ServiceAction {
-    ///         action_type: ServiceActionType::Restart,
-    ///         delay: Duration::from_secs(5),
-    ///     },
-    ///     ServiceAction {
-    ///         action_type: ServiceActionType::RunCommand,
-    ///         delay: Duration::from_secs(10),
-    ///     },
-    ///     ServiceAction {
-    ///         action_type: ServiceActionType::None,
-    ///         delay: Duration::default(),
-    ///     },
-    /// ];
+    /// This is the same as [`Service::start_and_wait`], except that the `cancel` flag is checked
+    /// while waiting, and [`Error::Cancelled`] is returned shortly after it is set, typically from
+    /// another thread. The service keeps starting in that case.
     ///
-    /// let failure_actions = ServiceFailureActions {
-    ///     reset_period: ServiceFailureResetPeriod::After(Duration::from_secs(86400)),
-    ///     reboot_msg: None,
-    ///     command: Some(OsString::from("ping 127.0.0.1")),
-    ///     actions: Some(actions),
+    /// Required permissions: [`ServiceAccess::START`], [`ServiceAccess::QUERY_STATUS`].
+    pub fn start_and_wait_cancellable<I, S>(
+        &self,
+        service_arguments: I,
+        timeout: Duration,
+        cancel: &AtomicBool,
+    ) -> crate::Result<ServiceStatus>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.start_and_wait_inner(service_arguments, timeout, Some(cancel))
+    }
+
+    fn start_and_wait_inner<I, S>(
+        &self,
+        service_arguments: I,
+        timeout: Duration,
+        cancel: Option<&AtomicBool>,
+    ) -> crate::Result<ServiceStatus>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.start(service_arguments)?;
+
+        let status = self.wait_for_inner(
+            |status| {
+                matches!(
+                    status.current_state,
+                    ServiceState::Running | ServiceState::Stopped
+                )
+            },
+            timeout,
+            cancel,
+        )?;
+        if status.current_state == ServiceState::Stopped {
+            Err(Error::ServiceFailedToStart(status.exit_code))
+        } else {
+            Ok(status)
+        }
+    }
+
+    /// Stop the service.
+    ///
+    /// Required permission: [`ServiceAccess::STOP`].
+    pub fn stop(&self) -> crate::Result<ServiceStatus> {
+        self.send_control_command(ServiceControl::Stop)
+    }
+
+    /// Stop the service, recording the reason in the system event log.
+    ///
+    /// The optional `comment` explaining the reason is truncated by the system to 128
+    /// characters.
+    ///
+    /// Returns [`Error::InvalidServiceControl`] if the service does not accept
+    /// [`ServiceControlAccept::STOP`], or [`Error::InvalidServiceInfo`] if the custom reason
+    /// codes are out of range or the [`StopReasonFlag::Custom`] is not combined with the custom
+    /// major and minor reasons, or vice versa.
+    ///
+    /// Required permission: [`ServiceAccess::STOP`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{
+    ///     ServiceAccess, StopReason, StopReasonFlag, StopReasonMajor, StopReasonMinor,
     /// };
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
     ///
-    /// my_service.update_failure_actions(failure_actions)?;
-    /// #    Ok(())
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::STOP)?;
+    /// my_service.stop_with_reason(
+    ///     StopReason {
+    ///         flag: StopReasonFlag::Planned,
+    ///         major: StopReasonMajor::Software,
+    ///         minor: StopReasonMinor::Upgrade,
+    ///     },
+    ///     Some("Upgrading to version 2.0"),
+    /// )?;
+    /// # Ok(())
     /// # }
     /// ```
-    pub fn update_failure_actions(&self, update: ServiceFailureActions) -> crate::Result<()> {
-        let mut raw_failure_actions =
-            unsafe { mem::zeroed::<Services::SERVICE_FAILURE_ACTIONSW>() };
+    pub fn stop_with_reason(
+        &self,
+        reason: StopReason,
+        comment: Option<impl AsRef<OsStr>>,
+    ) -> crate::Result<ServiceStatus> {
+        reason.validate()?;
+        let comment = comment
+            .map(|s| {
+                WideCString::from_os_str(s)
+                    .map_err(|_| Error::ArgumentHasNulByte("stop reason comment"))
+            })
+            .transpose()?;
+
+        let mut params = Services::SERVICE_CONTROL_STATUS_REASON_PARAMSW {
+            dwReason: reason.to_raw(),
+            pszComment: comment
+                .as_ref()
+                .map_or(ptr::null_mut(), |s| s.as_ptr() as _),
+            ServiceStatus: unsafe { mem::zeroed() },
+        };
 
-        let mut reboot_msg = to_wide_slice(update.reboot_msg)
-            .map_err(|_| Error::ArgumentHasNulByte("service action failures reboot message"))?;
-        let mut command = to_wide_slice(update.command)
-            .map_err(|_| Error::ArgumentHasNulByte("service action failures command"))?;
-        let mut sc_actions: Option<Vec<Services::SC_ACTION>> = update
-            .actions
-            .map(|actions| actions.iter().map(ServiceAction::to_raw).collect());
+        let success = unsafe {
+            Services::ControlServiceExW(
+                self.service_handle.raw_handle(),
+                Services::SERVICE_CONTROL_STOP,
+                Services::SERVICE_CONTROL_STATUS_REASON_INFO,
+                &mut params as *mut _ as *mut c_void,
+            )
+        };
 
-        raw_failure_actions.dwResetPeriod = update.reset_period.to_raw();
-        raw_failure_actions.lpRebootMsg = reboot_msg
-            .as_mut()
-            .map_or(ptr::null_mut(), |s| s.as_mut_ptr());
-        raw_failure_actions.lpCommand =
-            command.as_mut().map_or(ptr::null_mut(), |s| s.as_mut_ptr());
-        raw_failure_actions.cActions = sc_actions.as_ref().map_or(0, |v| v.len()) as u32;
-        raw_failure_actions.lpsaActions = sc_actions
-            .as_mut()
-            .map_or(ptr::null_mut(), |actions| actions.as_mut_ptr());
+        if success == 0 {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() == Some(ERROR_INVALID_SERVICE_CONTROL as i32) {
+                Err(Error::InvalidServiceControl)
+            } else {
+                Err(Error::Winapi(error))
+            }
+        } else {
+            Ok(ServiceStatus::from_raw_ex(params.ServiceStatus))
+        }
+    }
 
-        unsafe {
-            self.change_config2(
-                Services::SERVICE_CONFIG_FAILURE_ACTIONS,
-                &mut raw_failure_actions,
+    /// Stop the service and wait until it reaches the [`ServiceState::Stopped`] state.
+    ///
+    /// The service status is polled using the [`ServiceStatus::wait_hint`] reported by the
+    /// service as the interval, clamped between 100 milliseconds and 10 seconds.
+    ///
+    /// Returns [`Error::Timeout`] if the service is not stopped before the `timeout` elapses.
+    ///
+    /// Required permissions: [`ServiceAccess::STOP`], [`ServiceAccess::QUERY_STATUS`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service(
+    ///     "my_service",
+    ///     ServiceAccess::STOP | ServiceAccess::QUERY_STATUS,
+    /// )?;
+    /// my_service.stop_and_wait(Duration::from_secs(30))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stop_and_wait(&self, timeout: Duration) -> crate::Result<ServiceStatus> {
+        self.stop_and_wait_inner(timeout, None)
+    }
+
+    /// Stop the service and wait until it reaches the [`ServiceState::Stopped`] state, unless the
+    /// wait is cancelled.
+    ///
+    /// This is the same as [`Service::stop_and_wait`], except that the `cancel` flag is checked
+    /// while waiting, and [`Error::Cancelled`] is returned shortly after it is set, typically from
+    /// another thread. The service keeps stopping in that case.
+    ///
+    /// Required permissions: [`ServiceAccess::STOP`], [`ServiceAccess::QUERY_STATUS`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service(
+    ///     "my_service",
+    ///     ServiceAccess::STOP | ServiceAccess::QUERY_STATUS,
+    /// )?;
+    ///
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let cancel_after_delay = Arc::clone(&cancel);
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_secs(5));
+    ///     cancel_after_delay.store(true, Ordering::Relaxed);
+    /// });
+    /// my_service.stop_and_wait_cancellable(Duration::from_secs(30), &cancel)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stop_and_wait_cancellable(
+        &self,
+        timeout: Duration,
+        cancel: &AtomicBool,
+    ) -> crate::Result<ServiceStatus> {
+        self.stop_and_wait_inner(timeout, Some(cancel))
+    }
+
+    fn stop_and_wait_inner(
+        &self,
+        timeout: Duration,
+        cancel: Option<&AtomicBool>,
+    ) -> crate::Result<ServiceStatus> {
+        let status = self.stop()?;
+        if status.current_state == ServiceState::Stopped {
+            Ok(status)
+        } else {
+            self.poll_status(
+                status,
+                |status| status.current_state == ServiceState::Stopped,
+                timeout,
+                cancel,
             )
-            .map_err(Error::Winapi)
         }
     }
 
-    /// Set service description.
-    ///
-    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
-    pub fn set_description(&self, description: impl AsRef<OsStr>) -> crate::Result<()> {
-        let wide_str = WideCString::from_os_str(description)
-            .map_err(|_| Error::ArgumentHasNulByte("service description"))?;
-        let mut service_description = Services::SERVICE_DESCRIPTIONW {
-            lpDescription: wide_str.as_ptr() as *mut _,
-        };
+    /// Wait until the service status satisfies the `predicate`.
+    ///
+    /// The service status is polled using the [`ServiceStatus::wait_hint`] reported by the
+    /// service as the interval, clamped between 100 milliseconds and 10 seconds.
+    ///
+    /// Returns the first status that satisfied the `predicate`, or [`Error::Timeout`] if none did
+    /// before the `timeout` elapsed.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_STATUS`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service::{ServiceAccess, ServiceState};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::QUERY_STATUS)?;
+    /// my_service.wait_for(
+    ///     |status| status.current_state == ServiceState::Running,
+    ///     Duration::from_secs(30),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_for<F>(&self, predicate: F, timeout: Duration) -> crate::Result<ServiceStatus>
+    where
+        F: Fn(&ServiceStatus) -> bool,
+    {
+        self.wait_for_inner(predicate, timeout, None)
+    }
+
+    fn wait_for_inner<F>(
+        &self,
+        predicate: F,
+        timeout: Duration,
+        cancel: Option<&AtomicBool>,
+    ) -> crate::Result<ServiceStatus>
+    where
+        F: Fn(&ServiceStatus) -> bool,
+    {
+        let status = self.query_status()?;
+        if predicate(&status) {
+            Ok(status)
+        } else {
+            self.poll_status(status, predicate, timeout, cancel)
+        }
+    }
+
+    /// Restart the service and wait until it reaches the [`ServiceState::Running`] state.
+    ///
+    /// The service is stopped first unless it is already stopped, then it is started without
+    /// arguments. The `timeout` applies to the whole operation and the service status is polled
+    /// the same way as in [`Service::stop_and_wait`].
+    ///
+    /// Returns [`Error::StopTimeout`] if the service is not stopped, or [`Error::StartTimeout`]
+    /// if the service is not running before the `timeout` elapses.
+    ///
+    /// Required permissions: [`ServiceAccess::STOP`], [`ServiceAccess::START`],
+    /// [`ServiceAccess::QUERY_STATUS`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service(
+    ///     "my_service",
+    ///     ServiceAccess::STOP | ServiceAccess::START | ServiceAccess::QUERY_STATUS,
+    /// )?;
+    /// my_service.restart(Duration::from_secs(60))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn restart(&self, timeout: Duration) -> crate::Result<()> {
+        let start = Instant::now();
+
+        self.ensure_stopped(timeout).map_err(|e| match e {
+            Error::Timeout => Error::StopTimeout,
+            e => e,
+        })?;
+        self.ensure_running(timeout.saturating_sub(start.elapsed()))
+            .map_err(|e| match e {
+                Error::Timeout => Error::StartTimeout,
+                e => e,
+            })
+    }
+
+    /// Pause the service.
+    ///
+    /// Returns [`Error::InvalidServiceControl`] if the service does not accept
+    /// [`ServiceControlAccept::PAUSE_CONTINUE`].
+    ///
+    /// Required permission: [`ServiceAccess::PAUSE_CONTINUE`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::PAUSE_CONTINUE)?;
+    /// my_service.pause()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pause(&self) -> crate::Result<ServiceStatus> {
+        self.send_control_command(ServiceControl::Pause)
+    }
+
+    /// Resume the paused service.
+    ///
+    /// Returns [`Error::InvalidServiceControl`] if the service does not accept
+    /// [`ServiceControlAccept::PAUSE_CONTINUE`].
+    ///
+    /// Required permission: [`ServiceAccess::PAUSE_CONTINUE`].
+    pub fn resume(&self) -> crate::Result<ServiceStatus> {
+        self.send_control_command(ServiceControl::Continue)
+    }
+
+    /// Send user-defined control code.
+    ///
+    /// User-defined control codes are in the range of **128** to **255**, this is enforced by
+    /// [`UserEventCode`] before the control code is sent to the service.
+    ///
+    /// Required permission: [`ServiceAccess::USER_DEFINED_CONTROL`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceAccess, UserEventCode};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::USER_DEFINED_CONTROL)?;
+    /// let code = UserEventCode::from_raw(130)
+    ///     .map_err(|e| windows_service::Error::ParseValue("user event code", e))?;
+    /// my_service.notify(code)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn notify(&self, code: UserEventCode) -> crate::Result<ServiceStatus> {
+        self.send_control_command(ServiceControl::UserEvent(code))
+    }
+
+    /// Get the service status from the system.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_STATUS`].
+    pub fn query_status(&self) -> crate::Result<ServiceStatus> {
+        Ok(ServiceStatus::from_raw_ex(self.query_raw_status()?))
+    }
+
+    /// Get the id of the service process.
+    ///
+    /// Returns `None` if the service process is not running. This is a shortcut for
+    /// [`ServiceStatus::process_id`] of the status returned by [`Service::query_status`].
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_STATUS`].
+    pub fn process_id(&self) -> crate::Result<Option<u32>> {
+        Ok(self.query_status()?.process_id)
+    }
+
+    /// Private helper to query the raw service status from the system.
+    fn query_raw_status(&self) -> crate::Result<Services::SERVICE_STATUS_PROCESS> {
+        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS_PROCESS>() };
+        let mut bytes_needed: u32 = 0;
+        let success = unsafe {
+            Services::QueryServiceStatusEx(
+                self.service_handle.raw_handle(),
+                Services::SC_STATUS_PROCESS_INFO,
+                &mut raw_status as *mut _ as _,
+                std::mem::size_of::<Services::SERVICE_STATUS_PROCESS>() as u32,
+                &mut bytes_needed,
+            )
+        };
+        if success == 0 {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(raw_status)
+        }
+    }
+
+    /// Mark the service for deletion from the service control manager database.
+    ///
+    /// The database entry is not removed until all open handles to the service have been closed
+    /// and the service is stopped. If the service is not or cannot be stopped, the database entry
+    /// is removed when the system is restarted. This function will return an error if the service
+    /// has already been marked for deletion.
+    ///
+    /// Until the service is removed, attempts to open it or change its configuration through
+    /// other handles may fail with `ERROR_SERVICE_MARKED_FOR_DELETE`.
+    ///
+    /// The function does not consume `self`, so that the service can still be queried and stopped
+    /// through this handle after it is marked for deletion. Make sure to `drop` the handle before
+    /// waiting for the service to be removed. (See `uninstall_service.rs` example)
+    ///
+    /// Required permission: [`ServiceAccess::DELETE`].
+    pub fn delete(&self) -> crate::Result<()> {
+        let success = unsafe { Services::DeleteService(self.service_handle.raw_handle()) };
+        if success == 0 {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enumerate services that depend on this service.
+    ///
+    /// Services are returned in the reverse order of the start order, so the services that
+    /// depend on other services in the list precede them.
+    ///
+    /// Required permission: [`ServiceAccess::ENUMERATE_DEPENDENTS`].
+    pub fn enumerate_dependent_services(
+        &self,
+        service_state: ServiceStateFilter,
+    ) -> crate::Result<Vec<ServiceEntry>> {
+        let mut buffer: Vec<u8> = Vec::new();
+
+        loop {
+            let mut bytes_needed: u32 = 0;
+            let mut services_returned: u32 = 0;
+
+            let success = unsafe {
+                Services::EnumDependentServicesW(
+                    self.service_handle.raw_handle(),
+                    service_state.to_raw(),
+                    if buffer.is_empty() {
+                        ptr::null_mut()
+                    } else {
+                        buffer.as_mut_ptr() as _
+                    },
+                    u32::try_from(buffer.len()).expect("size must fit in u32"),
+                    &mut bytes_needed,
+                    &mut services_returned,
+                )
+            };
+
+            if success == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_MORE_DATA as i32) {
+                    return Err(Error::Winapi(error));
+                }
+                buffer.resize(usize::try_from(bytes_needed).unwrap(), 0);
+            } else {
+                // Never trust the number of entries beyond what fits in the buffer, e.g. when the
+                // response comes from a remote computer.
+                let entries_size = (services_returned as usize)
+                    .checked_mul(mem::size_of::<Services::ENUM_SERVICE_STATUSW>());
+                if entries_size.map_or(true, |size| size > buffer.len()) {
+                    return Err(Error::ParseValue(
+                        "number of service entries",
+                        ParseRawError::InvalidInteger(services_returned),
+                    ));
+                }
+
+                let entries = buffer.as_ptr() as *const Services::ENUM_SERVICE_STATUSW;
+                return Ok((0..services_returned as usize)
+                    .map(|i| unsafe { ServiceEntry::from_raw(ptr::read_unaligned(entries.add(i))) })
+                    .collect());
+            }
+        }
+    }
+
+    /// Query the security descriptor of the service.
+    ///
+    /// Returns the raw bytes of the descriptor in the self-relative format.
+    ///
+    /// Required permission: [`ServiceAccess::READ_CONTROL`]. Querying the
+    /// [`SecurityInformation::SACL`] requires [`ServiceAccess::ACCESS_SYSTEM_SECURITY`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{SecurityInformation, ServiceAccess};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::READ_CONTROL)?;
+    /// let descriptor = my_service.query_security(SecurityInformation::DACL)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_security(
+        &self,
+        security_information: SecurityInformation,
+    ) -> crate::Result<Vec<u8>> {
+        let mut buffer: Vec<u8> = Vec::new();
+
+        loop {
+            let mut bytes_needed: u32 = 0;
+
+            let success = unsafe {
+                Services::QueryServiceObjectSecurity(
+                    self.service_handle.raw_handle(),
+                    security_information.bits(),
+                    buffer.as_mut_ptr() as _,
+                    u32::try_from(buffer.len()).expect("size must fit in u32"),
+                    &mut bytes_needed,
+                )
+            };
+
+            if success == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) {
+                    return Err(Error::Winapi(error));
+                }
+                buffer.resize(usize::try_from(bytes_needed).unwrap(), 0);
+            } else {
+                return Ok(buffer);
+            }
+        }
+    }
+
+    /// Change the security descriptor of the service.
+    ///
+    /// The `descriptor` holds the raw bytes of the descriptor in the self-relative format,
+    /// as returned by [`Service::query_security`]. Only the parts of the descriptor specified by
+    /// `security_information` are applied.
+    ///
+    /// Required permission: [`ServiceAccess::WRITE_DAC`] when changing the
+    /// [`SecurityInformation::DACL`], [`ServiceAccess::WRITE_OWNER`] when changing the
+    /// [`SecurityInformation::OWNER`] or the [`SecurityInformation::GROUP`], and
+    /// [`ServiceAccess::ACCESS_SYSTEM_SECURITY`] when changing the [`SecurityInformation::SACL`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `ERROR_INVALID_SECURITY_DESCR` if the `descriptor` does not hold a valid security
+    /// descriptor.
+    pub fn set_security(
+        &self,
+        security_information: SecurityInformation,
+        descriptor: &[u8],
+    ) -> crate::Result<()> {
+        if !is_valid_security_descriptor(descriptor, security_information) {
+            return Err(Error::Winapi(io::Error::from_raw_os_error(
+                ERROR_INVALID_SECURITY_DESCR as i32,
+            )));
+        }
+
+        let success = unsafe {
+            Services::SetServiceObjectSecurity(
+                self.service_handle.raw_handle(),
+                security_information.bits(),
+                descriptor.as_ptr() as *mut c_void,
+            )
+        };
+
+        if success == 0 {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Subscribe for the service status changes.
+    ///
+    /// The subscription takes ownership of the service, since the service handle must remain open
+    /// for as long as the notifications are pending.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_STATUS`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    /// use windows_service::service_notify::ServiceNotifyMask;
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::QUERY_STATUS)?;
+    /// let mut subscription = my_service.subscribe_status_changes(
+    ///     ServiceNotifyMask::RUNNING | ServiceNotifyMask::STOPPED,
+    /// )?;
+    ///
+    /// while let Some(notify) = subscription.next(Duration::from_secs(30))? {
+    ///     println!("Service state changed: {:?}", notify.status.current_state);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe_status_changes(
+        self,
+        mask: ServiceNotifyMask,
+    ) -> crate::Result<StatusChangeSubscription> {
+        StatusChangeSubscription::new(self, mask)
+    }
+
+    /// Get the service config from the system.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn query_config(&self) -> crate::Result<ServiceConfig> {
+        // The first call with an empty buffer reports the size needed for the config.
+        let mut data: Vec<u8> = Vec::new();
+
+        loop {
+            let mut bytes_needed: u32 = 0;
+
+            let success = unsafe {
+                Services::QueryServiceConfigW(
+                    self.service_handle.raw_handle(),
+                    data.as_mut_ptr() as _,
+                    u32::try_from(data.len()).expect("size must fit in u32"),
+                    &mut bytes_needed,
+                )
+            };
+
+            if success == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) {
+                    return Err(Error::Winapi(error));
+                }
+                data.resize(usize::try_from(bytes_needed).unwrap(), 0);
+            } else {
+                return unsafe {
+                    let raw_config = data.as_ptr() as *const Services::QUERY_SERVICE_CONFIGW;
+                    ServiceConfig::from_raw(*raw_config)
+                };
+            }
+        }
+    }
+
+    /// Update the service config.
+    /// Caveat: You cannot reset the account name/password or the load ordering group by passing
+    /// NULL.
+    ///
+    /// This implementation does not currently expose the full flexibility of the
+    /// `ChangeServiceConfigW` API. When calling the API it's possible to pass NULL in place of
+    /// any of the string arguments to indicate that they should not be updated.
+    ///
+    /// If we wanted to support this we wouldn't be able to reuse the `ServiceInfo` struct.
+    /// Use [`Service::update_config`] to update only some of the fields.
+    pub fn change_config(&self, service_info: &ServiceInfo) -> crate::Result<()> {
+        let raw_info = RawServiceInfo::new(service_info)?;
+        // A null pointer would keep the current dependencies, whereas an empty string removes
+        // them, so that the service has no dependencies when none are given.
+        let dependencies = raw_info
+            .dependencies
+            .clone()
+            .unwrap_or_else(|| WideString::from_vec([0, 0]));
+        let success = unsafe {
+            Services::ChangeServiceConfigW(
+                self.service_handle.raw_handle(),
+                raw_info.service_type,
+                raw_info.start_type,
+                raw_info.error_control,
+                raw_info.launch_command.as_ptr(),
+                raw_info
+                    .load_order_group
+                    .as_ref()
+                    .map_or(ptr::null(), |s| s.as_ptr()),
+                ptr::null_mut(), // tag id within the load ordering group
+                dependencies.as_ptr(),
+                raw_info
+                    .account_name
+                    .as_ref()
+                    .map_or(ptr::null(), |s| s.as_ptr()),
+                raw_info
+                    .account_password
+                    .as_ref()
+                    .map_or(ptr::null(), |s| s.as_ptr()),
+                raw_info.display_name.as_ptr(),
+            )
+        };
+
+        if success == 0 {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Change the account that the service runs as, or only its password, leaving the rest of
+    /// the config unchanged.
+    ///
+    /// Pass `None` as the `account_name` to only change the password, e.g. to rotate it. Pass
+    /// `LocalSystem` to run the service as the local system account again. The built-in
+    /// accounts, such as `LocalSystem` or `NT AUTHORITY\NetworkService`, do not have a password, so
+    /// an empty password is passed to the system for them unless the `password` is given.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{SecretOsString, ServiceAccess};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::CHANGE_CONFIG)?;
+    /// my_service.change_account(Some(r".\my_user"), Some(SecretOsString::from("password")))?;
+    /// my_service.change_account(Some("LocalSystem"), None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn change_account(
+        &self,
+        account_name: Option<impl AsRef<OsStr>>,
+        password: Option<SecretOsString>,
+    ) -> crate::Result<()> {
+        let account_name = account_name.map(|name| name.as_ref().to_os_string());
+        let is_builtin_account = account_name.as_ref().map_or(false, |name| {
+            is_local_system_account(name)
+                || name
+                    .to_string_lossy()
+                    .to_ascii_lowercase()
+                    .starts_with("nt authority\\")
+        });
+        let password = match password {
+            None if is_builtin_account => Some(SecretOsString::default()),
+            password => password,
+        };
+
+        self.update_config(&ServiceConfigChange {
+            account_name,
+            account_password: password,
+            ..Default::default()
+        })
+    }
+
+    /// Set the user-friendly service name, leaving the rest of the config unchanged.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::CHANGE_CONFIG)?;
+    /// my_service.set_display_name("Mein Dienst")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_display_name(&self, display_name: impl AsRef<OsStr>) -> crate::Result<()> {
+        self.update_config(&ServiceConfigChange {
+            display_name: Some(display_name.as_ref().to_os_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Update only the given fields of the service config.
+    ///
+    /// Unlike [`Service::change_config`], the fields of [`ServiceConfigChange`] set to `None` are
+    /// left unchanged.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`], and also
+    /// [`ServiceAccess::QUERY_CONFIG`] when the executable path is changed without the service
+    /// type, since the current service type determines how the path is escaped.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceAccess, ServiceConfigChange, ServiceStartType};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::CHANGE_CONFIG)?;
+    /// my_service.update_config(&ServiceConfigChange {
+    ///     start_type: Some(ServiceStartType::AutoStart),
+    ///     ..Default::default()
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_config(&self, config_change: &ServiceConfigChange) -> crate::Result<()> {
+        let display_name = to_wide(config_change.display_name.as_ref())
+            .map_err(|_| Error::ArgumentHasNulByte("display name"))?;
+        let launch_command = match config_change.binary {
+            Some(ref binary @ ServiceBinary::Executable { .. }) => {
+                let service_type = match config_change.service_type {
+                    Some(service_type) => service_type,
+                    None => self.query_config()?.service_type,
+                };
+                Some(binary.to_launch_command(service_type)?)
+            }
+            // The raw command line does not depend on the service type.
+            Some(ref binary) => Some(binary.to_launch_command(ServiceType::OWN_PROCESS)?),
+            None => None,
+        };
+        let load_order_group = to_wide(config_change.load_order_group.as_ref())
+            .map_err(|_| Error::ArgumentHasNulByte("load order group"))?;
+        let dependencies = match config_change.dependencies {
+            // An empty string removes all dependencies, a null pointer keeps them unchanged.
+            Some(ref dependencies) => Some(
+                joined_dependencies(dependencies)?.unwrap_or_else(|| WideString::from_vec([0, 0])),
+            ),
+            None => None,
+        };
+        let account_name = to_wide(config_change.account_name.as_ref())
+            .map_err(|_| Error::ArgumentHasNulByte("account name"))?;
+        let account_password = secret_to_wide(config_change.account_password.as_ref())?;
+
+        let success = unsafe {
+            Services::ChangeServiceConfigW(
+                self.service_handle.raw_handle(),
+                config_change
+                    .service_type
+                    .map_or(Services::SERVICE_NO_CHANGE, |service_type| {
+                        service_type.bits()
+                    }),
+                config_change
+                    .start_type
+                    .map_or(Services::SERVICE_NO_CHANGE, |start_type| {
+                        start_type.to_raw()
+                    }),
+                config_change
+                    .error_control
+                    .map_or(Services::SERVICE_NO_CHANGE, |error_control| {
+                        error_control.to_raw()
+                    }),
+                launch_command.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                load_order_group
+                    .as_ref()
+                    .map_or(ptr::null(), |s| s.as_ptr()),
+                ptr::null_mut(), // tag id within the load ordering group
+                dependencies.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                account_name.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                account_password
+                    .as_ref()
+                    .map_or(ptr::null(), |s| s.as_ptr()),
+                display_name.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            )
+        };
+
+        if success == 0 {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Configure failure actions to run when the service terminates before reporting the
+    /// [`ServiceState::Stopped`] back to the system or if it exits with non-zero
+    /// [`ServiceExitCode`].
+    ///
+    /// By default the failure actions only run when the service process terminates unexpectedly.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    ///
+    /// Please refer to MSDN for more info:\
+    /// <https://docs.microsoft.com/en-us/windows/win32/api/winsvc/ns-winsvc-_service_failure_actions_flag>
+    pub fn set_failure_actions_on_non_crash_failures(&self, enabled: bool) -> crate::Result<()> {
+        let mut raw_failure_actions_flag =
+            unsafe { mem::zeroed::<Services::SERVICE_FAILURE_ACTIONS_FLAG>() };
+
+        raw_failure_actions_flag.fFailureActionsOnNonCrashFailures = if enabled { 1 } else { 0 };
+
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_FAILURE_ACTIONS_FLAG,
+                &mut raw_failure_actions_flag,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the system for the boolean indication that the service is configured to run failure
+    /// actions on non-crash failures.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_failure_actions_on_non_crash_failures(&self) -> crate::Result<bool> {
+        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+
+        let raw_failure_actions_flag: Services::SERVICE_FAILURE_ACTIONS_FLAG = unsafe {
+            self.query_config2(Services::SERVICE_CONFIG_FAILURE_ACTIONS_FLAG, &mut data)
+                .map_err(Error::Winapi)?
+        };
+        Ok(raw_failure_actions_flag.fFailureActionsOnNonCrashFailures != 0)
+    }
+
+    /// Query the system for the service's SID type information.
+    ///
+    /// The service must be open with the [`ServiceAccess::QUERY_CONFIG`]
+    /// access permission prior to calling this method.
+    pub fn get_config_service_sid_info(&self) -> crate::Result<ServiceSidType> {
+        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+
+        let raw_sid_info: Services::SERVICE_SID_INFO = unsafe {
+            self.query_config2(Services::SERVICE_CONFIG_SERVICE_SID_INFO, &mut data)
+                .map_err(Error::Winapi)?
+        };
+        ServiceSidType::from_raw(raw_sid_info.dwServiceSidType)
+            .map_err(|e| Error::ParseValue("service SID type", e))
+    }
+
+    /// Require the system to set the service's SID type information to the
+    /// provided value.
+    ///
+    /// The service must be open with the [`ServiceAccess::CHANGE_CONFIG`]
+    /// access permission prior to calling this method.
+    pub fn set_config_service_sid_info(
+        &self,
+        service_sid_type: ServiceSidType,
+    ) -> crate::Result<()> {
+        let mut raw_sid_info = Services::SERVICE_SID_INFO {
+            dwServiceSidType: service_sid_type.to_raw(),
+        };
+        unsafe {
+            self.change_config2(Services::SERVICE_CONFIG_SERVICE_SID_INFO, &mut raw_sid_info)
+                .map_err(Error::Winapi)
+        }
+    }
+
+    /// Set the protection level of the service process.
+    ///
+    /// The service binary must be signed appropriately to run with the requested protection
+    /// level. The system denies access when the protection level cannot be changed, for
+    /// example when lowering the protection level of a protected service.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    pub fn set_launch_protected(
+        &self,
+        launch_protection: ServiceLaunchProtection,
+    ) -> crate::Result<()> {
+        let mut raw_launch_protected = Services::SERVICE_LAUNCH_PROTECTED_INFO {
+            dwLaunchProtected: launch_protection.to_raw(),
+        };
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_LAUNCH_PROTECTED,
+                &mut raw_launch_protected,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the protection level of the service process.
+    ///
+    /// Returns [`ServiceLaunchProtection::None`] on the systems that do not support protected
+    /// services before Windows 8.1, since no service can be protected there.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_launch_protected(&self) -> crate::Result<ServiceLaunchProtection> {
+        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+
+        let result: io::Result<Services::SERVICE_LAUNCH_PROTECTED_INFO> =
+            unsafe { self.query_config2(Services::SERVICE_CONFIG_LAUNCH_PROTECTED, &mut data) };
+        let raw_launch_protected = match result {
+            Ok(raw_launch_protected) => raw_launch_protected,
+            Err(e) if e.raw_os_error() == Some(ERROR_INVALID_LEVEL as i32) => {
+                return Ok(ServiceLaunchProtection::None)
+            }
+            Err(e) => return Err(Error::Winapi(e)),
+        };
+        ServiceLaunchProtection::from_raw(raw_launch_protected.dwLaunchProtected)
+            .map_err(|e| Error::ParseValue("service launch protection", e))
+    }
+
+    /// Set the preferred NUMA node of the service process.
+    ///
+    /// Pass `None` to remove the node preference. This setting is only supported for services
+    /// that run in their own process.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    pub fn set_preferred_node(&self, preferred_node: Option<u16>) -> crate::Result<()> {
+        let mut raw_preferred_node = Services::SERVICE_PREFERRED_NODE_INFO {
+            usPreferredNode: preferred_node.unwrap_or(0),
+            fDelete: preferred_node.is_none() as u8,
+        };
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_PREFERRED_NODE,
+                &mut raw_preferred_node,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the preferred NUMA node of the service process.
+    ///
+    /// Returns `None` if the service has no node preference.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_preferred_node(&self) -> crate::Result<Option<u16>> {
+        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+
+        let raw_preferred_node: Services::SERVICE_PREFERRED_NODE_INFO = unsafe {
+            self.query_config2(Services::SERVICE_CONFIG_PREFERRED_NODE, &mut data)
+                .map_err(Error::Winapi)?
+        };
+        if raw_preferred_node.fDelete != 0 {
+            Ok(None)
+        } else {
+            Ok(Some(raw_preferred_node.usPreferredNode))
+        }
+    }
+
+    /// Query the configured failure actions for the service.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_failure_actions(&self) -> crate::Result<ServiceFailureActions> {
+        unsafe {
+            let mut data = Vec::new();
+
+            let raw_failure_actions: Services::SERVICE_FAILURE_ACTIONSW = self
+                .query_variable_config2(Services::SERVICE_CONFIG_FAILURE_ACTIONS, &mut data)
+                .map_err(Error::Winapi)?;
+
+            ServiceFailureActions::from_raw(raw_failure_actions)
+        }
+    }
+
+    /// Update failure actions.
+    ///
+    /// Pass `None` for optional fields to keep the corresponding fields unchanged, or pass an empty
+    /// value to reset them.
+    ///
+    /// Returns [`Error::InvalidServiceInfo`] if an action delay or the reset period is too long to
+    /// be passed to the system.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`], and [`ServiceAccess::START`] when
+    /// any of the actions is [`ServiceActionType::Restart`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::ffi::OsString;
+    /// use std::time::Duration;
+    /// use windows_service::service::{
+    ///     ServiceAccess, ServiceAction, ServiceActionType, ServiceFailureActions,
+    ///     ServiceFailureResetPeriod,
+    /// };
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service(
+    ///     "my_service",
+    ///     ServiceAccess::START | ServiceAccess::CHANGE_CONFIG,
+    /// )?;
+    ///
+    /// let actions = vec![
+    ///     ServiceAction {
+    ///         action_type: ServiceActionType::Restart,
+    ///         delay: Duration::from_secs(5),
+    ///     },
+    ///     ServiceAction {
+    ///         action_type: ServiceActionType::RunCommand,
+    ///         delay: Duration::from_secs(10),
+    ///     },
+    ///     ServiceAction {
+    ///         action_type: ServiceActionType::None,
+    ///         delay: Duration::default(),
+    ///     },
+    /// ];
+    ///
+    /// let failure_actions = ServiceFailureActions {
+    ///     reset_period: ServiceFailureResetPeriod::After(Duration::from_secs(86400)),
+    ///     reboot_msg: None,
+    ///     command: Some(OsString::from("ping 127.0.0.1")),
+    ///     actions: Some(actions),
+    /// };
+    ///
+    /// my_service.update_failure_actions(failure_actions)?;
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn update_failure_actions(&self, update: ServiceFailureActions) -> crate::Result<()> {
+        let mut raw_failure_actions =
+            unsafe { mem::zeroed::<Services::SERVICE_FAILURE_ACTIONSW>() };
+
+        let mut reboot_msg = to_wide_slice(update.reboot_msg)
+            .map_err(|_| Error::ArgumentHasNulByte("service action failures reboot message"))?;
+        let mut command = to_wide_slice(update.command)
+            .map_err(|_| Error::ArgumentHasNulByte("service action failures command"))?;
+        let mut sc_actions: Option<Vec<Services::SC_ACTION>> = update
+            .actions
+            .map(|actions| actions.iter().map(ServiceAction::to_raw).collect())
+            .transpose()?;
+
+        raw_failure_actions.dwResetPeriod = update.reset_period.to_raw()?;
+        raw_failure_actions.lpRebootMsg = reboot_msg
+            .as_mut()
+            .map_or(ptr::null_mut(), |s| s.as_mut_ptr());
+        raw_failure_actions.lpCommand =
+            command.as_mut().map_or(ptr::null_mut(), |s| s.as_mut_ptr());
+        raw_failure_actions.cActions = sc_actions.as_ref().map_or(0, |v| v.len()) as u32;
+        raw_failure_actions.lpsaActions = sc_actions
+            .as_mut()
+            .map_or(ptr::null_mut(), |actions| actions.as_mut_ptr());
+
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_FAILURE_ACTIONS,
+                &mut raw_failure_actions,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Configure the service to be restarted whenever it fails.
+    ///
+    /// This is a shortcut for [`Service::update_failure_actions`] with a single
+    /// [`ServiceActionType::Restart`] action, which the system repeats for every subsequent
+    /// failure, so the service is restarted after `delay` each time it fails. The failure count
+    /// is reset once the service runs for `reset_period` without failing. The reboot message and
+    /// the command are left unchanged.
+    ///
+    /// Returns [`Error::InvalidServiceInfo`] if `delay` does not fit as milliseconds, or
+    /// `reset_period` as seconds, in a `u32`.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`] and [`ServiceAccess::START`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service(
+    ///     "my_service",
+    ///     ServiceAccess::CHANGE_CONFIG | ServiceAccess::START,
+    /// )?;
+    /// my_service.enable_auto_restart(Duration::from_secs(5), Duration::from_secs(86400))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enable_auto_restart(
+        &self,
+        delay: Duration,
+        reset_period: Duration,
+    ) -> crate::Result<()> {
+        self.update_failure_actions(auto_restart_failure_actions(delay, reset_period))
+    }
+
+    /// Set the triggers of the service.
+    ///
+    /// Replaces all existing triggers, pass an empty [`ServiceTriggerInfo`] to remove them.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{
+    ///     ServiceAccess, ServiceTrigger, ServiceTriggerAction, ServiceTriggerInfo,
+    ///     ServiceTriggerSubtype, ServiceTriggerType,
+    /// };
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_service = manager.open_service("my_service", ServiceAccess::CHANGE_CONFIG)?;
+    /// my_service.set_trigger_info(&ServiceTriggerInfo {
+    ///     triggers: vec![ServiceTrigger {
+    ///         trigger_type: ServiceTriggerType::IpAddressAvailability,
+    ///         action: ServiceTriggerAction::ServiceStart,
+    ///         trigger_subtype: Some(ServiceTriggerSubtype::FirstIpAddressArrival),
+    ///         data_items: vec![],
+    ///     }],
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_trigger_info(&self, trigger_info: &ServiceTriggerInfo) -> crate::Result<()> {
+        let mut raw_trigger_info = RawServiceTriggerInfo::new(trigger_info)?;
+        let mut raw = Services::SERVICE_TRIGGER_INFO {
+            cTriggers: u32::try_from(raw_trigger_info.triggers.len())
+                .expect("size must fit in u32"),
+            pTriggers: if raw_trigger_info.triggers.is_empty() {
+                ptr::null_mut()
+            } else {
+                raw_trigger_info.triggers.as_mut_ptr()
+            },
+            pReserved: ptr::null_mut(),
+        };
+
+        unsafe {
+            self.change_config2(Services::SERVICE_CONFIG_TRIGGER_INFO, &mut raw)
+                .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the triggers of the service.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_trigger_info(&self) -> crate::Result<ServiceTriggerInfo> {
+        let mut data = Vec::new();
+
+        unsafe {
+            let raw_trigger_info: Services::SERVICE_TRIGGER_INFO = self
+                .query_variable_config2(Services::SERVICE_CONFIG_TRIGGER_INFO, &mut data)
+                .map_err(Error::Winapi)?;
+
+            ServiceTriggerInfo::from_raw(raw_trigger_info)
+        }
+    }
+
+    /// Query whether the service has any triggers without decoding them.
+    ///
+    /// The buffer passed to the system only fits the trigger count, the system reports that it
+    /// is too small when there are any triggers to return along with it.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn has_triggers(&self) -> crate::Result<bool> {
+        let mut data = vec![0u8; mem::size_of::<Services::SERVICE_TRIGGER_INFO>()];
+
+        let result: io::Result<Services::SERVICE_TRIGGER_INFO> =
+            unsafe { self.query_config2(Services::SERVICE_CONFIG_TRIGGER_INFO, &mut data) };
+        match result {
+            Ok(raw_trigger_info) => Ok(raw_trigger_info.cTriggers > 0),
+            Err(e) if e.raw_os_error() == Some(ERROR_INSUFFICIENT_BUFFER as i32) => Ok(true),
+            Err(e) => Err(Error::Winapi(e)),
+        }
+    }
+
+    /// Set service description.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    pub fn set_description(&self, description: impl AsRef<OsStr>) -> crate::Result<()> {
+        let wide_str = WideCString::from_os_str(description)
+            .map_err(|_| Error::ArgumentHasNulByte("service description"))?;
+        let mut service_description = Services::SERVICE_DESCRIPTIONW {
+            lpDescription: wide_str.as_ptr() as *mut _,
+        };
+
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_DESCRIPTION,
+                &mut service_description,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the service description.
+    ///
+    /// Returns `None` if the service has no description.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_description(&self) -> crate::Result<Option<OsString>> {
+        let mut data = Vec::new();
+
+        let raw_description: Services::SERVICE_DESCRIPTIONW = unsafe {
+            self.query_variable_config2(Services::SERVICE_CONFIG_DESCRIPTION, &mut data)
+                .map_err(Error::Winapi)?
+        };
+
+        let description = ptr::NonNull::new(raw_description.lpDescription)
+            .map(|wrapped_ptr| {
+                unsafe { WideCStr::from_ptr_str(wrapped_ptr.as_ptr()) }.to_os_string()
+            })
+            .filter(|description| !description.is_empty());
+        Ok(description)
+    }
+
+    /// Set if an auto-start service should be delayed.
+    ///
+    /// If true, the service is started after other auto-start services are started plus a short delay.
+    /// Otherwise, the service is started during system boot. The default is false. This setting is
+    /// ignored unless the service is an auto-start service.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    pub fn set_delayed_auto_start(&self, delayed: bool) -> crate::Result<()> {
+        let mut delayed = Services::SERVICE_DELAYED_AUTO_START_INFO {
+            fDelayedAutostart: delayed as i32,
+        };
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+                &mut delayed,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Set the privileges required by the service.
+    ///
+    /// Privileges are specified by names, such as `SeChangeNotifyPrivilege`. Pass an empty
+    /// collection to remove all required privileges.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    pub fn set_required_privileges<I, S>(&self, privileges: I) -> crate::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let privileges: Vec<S> = privileges.into_iter().collect();
+        let mut wide_privileges = double_nul_terminated::from_slice(&privileges)
+            .map_err(|_| Error::ArgumentHasNulByte("required privilege"))?
+            .unwrap_or_else(|| WideString::from_vec([0, 0]));
+
+        let mut required_privileges = Services::SERVICE_REQUIRED_PRIVILEGES_INFOW {
+            pmszRequiredPrivileges: wide_privileges.as_mut_ptr(),
+        };
+        unsafe {
+            self.change_config2(
+                Services::SERVICE_CONFIG_REQUIRED_PRIVILEGES_INFO,
+                &mut required_privileges,
+            )
+            .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the privileges required by the service.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_required_privileges(&self) -> crate::Result<Vec<OsString>> {
+        let mut data = Vec::new();
+
+        unsafe {
+            let required_privileges: Services::SERVICE_REQUIRED_PRIVILEGES_INFOW = self
+                .query_variable_config2(
+                    Services::SERVICE_CONFIG_REQUIRED_PRIVILEGES_INFO,
+                    &mut data,
+                )
+                .map_err(Error::Winapi)?;
+            Ok(double_nul_terminated::parse_str_ptr(
+                required_privileges.pmszRequiredPrivileges,
+            ))
+        }
+    }
+
+    /// Query the system for the boolean indication that an auto-start service is delayed.
+    ///
+    /// This setting only has effect when the service start type is
+    /// [`ServiceStartType::AutoStart`].
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_delayed_auto_start(&self) -> crate::Result<bool> {
+        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+
+        let raw_delayed: Services::SERVICE_DELAYED_AUTO_START_INFO = unsafe {
+            self.query_config2(Services::SERVICE_CONFIG_DELAYED_AUTO_START_INFO, &mut data)
+                .map_err(Error::Winapi)?
+        };
+        Ok(raw_delayed.fDelayedAutostart != 0)
+    }
+
+    /// Set the preshutdown timeout value of the service.
+    ///
+    /// When the system prepares to shutdown, the service control manager will send [`ServiceControl::Preshutdown`]
+    /// to any service that accepts [`ServiceControlAccept::PRESHUTDOWN`] and block shutdown until either the
+    /// service is stopped or the preshutdown timeout has elapsed. The default value is 180 seconds on releases
+    /// prior to Windows 10 build 15063, and 10 seconds afterwards. This value is irrelevant unless the service
+    /// handles [`ServiceControl::Preshutdown`].
+    ///
+    /// Returns [`Error::InvalidServiceInfo`] if the timeout does not fit as milliseconds in a
+    /// `u32`, excluding `INFINITE`.
+    ///
+    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
+    pub fn set_preshutdown_timeout(&self, timeout: Duration) -> crate::Result<()> {
+        let mut timeout = Services::SERVICE_PRESHUTDOWN_INFO {
+            dwPreshutdownTimeout: finite_millis(timeout)
+                .ok_or(Error::InvalidServiceInfo("preshutdown timeout is too long"))?,
+        };
+        unsafe {
+            self.change_config2(Services::SERVICE_CONFIG_PRESHUTDOWN_INFO, &mut timeout)
+                .map_err(Error::Winapi)
+        }
+    }
+
+    /// Query the preshutdown timeout value of the service.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    pub fn get_preshutdown_timeout(&self) -> crate::Result<Duration> {
+        let mut data = vec![0u8; MAX_QUERY_BUFFER_SIZE];
+
+        let raw_timeout: Services::SERVICE_PRESHUTDOWN_INFO = unsafe {
+            self.query_config2(Services::SERVICE_CONFIG_PRESHUTDOWN_INFO, &mut data)
+                .map_err(Error::Winapi)?
+        };
+        Ok(Duration::from_millis(
+            raw_timeout.dwPreshutdownTimeout as u64,
+        ))
+    }
+
+    /// Query the extended configuration of the service.
+    ///
+    /// This is a shortcut for querying the description, delayed auto-start, SID type, required
+    /// privileges, preshutdown timeout, failure actions, failure actions flag and launch
+    /// protection of the service at once. The settings that the system does not support are
+    /// returned as `None`.
+    ///
+    /// Required permission: [`ServiceAccess::QUERY_CONFIG`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::ServiceAccess;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let service = manager.open_service("my_service", ServiceAccess::QUERY_CONFIG)?;
+    /// let config = service.query_extended_config()?;
+    /// println!("Required privileges: {:?}", config.required_privileges);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_extended_config(&self) -> crate::Result<ExtendedServiceConfig> {
+        Ok(ExtendedServiceConfig {
+            description: optional_config2(self.get_description())?.flatten(),
+            delayed_auto_start: optional_config2(self.get_delayed_auto_start())?,
+            sid_type: optional_config2(self.get_config_service_sid_info())?,
+            required_privileges: optional_config2(self.get_required_privileges())?,
+            preshutdown_timeout: optional_config2(self.get_preshutdown_timeout())?,
+            failure_actions: optional_config2(self.get_failure_actions())?,
+            failure_actions_on_non_crash_failures: optional_config2(
+                self.get_failure_actions_on_non_crash_failures(),
+            )?,
+            launch_protected: self.get_launch_protected()?,
+        })
+    }
+
+    /// Private helper to send the control commands to the system.
+    fn send_control_command(&self, command: ServiceControl) -> crate::Result<ServiceStatus> {
+        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS>() };
+        let success = unsafe {
+            Services::ControlService(
+                self.service_handle.raw_handle(),
+                command.raw_service_control_type(),
+                &mut raw_status,
+            )
+        };
+
+        if success == 0 {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() == Some(ERROR_INVALID_SERVICE_CONTROL as i32) {
+                Err(Error::InvalidServiceControl)
+            } else {
+                Err(Error::Winapi(error))
+            }
+        } else {
+            Ok(ServiceStatus::from_raw(raw_status))
+        }
+    }
+
+    /// Private helper to stop the service unless it is already stopped, and wait until it is
+    /// stopped.
+    pub(crate) fn ensure_stopped(&self, timeout: Duration) -> crate::Result<()> {
+        let mut status = self.query_status()?;
+        if status.current_state == ServiceState::Stopped {
+            return Ok(());
+        }
+        if status.current_state != ServiceState::StopPending {
+            status = self.stop()?;
+        }
+        if status.current_state != ServiceState::Stopped {
+            self.poll_status(
+                status,
+                |status| status.current_state == ServiceState::Stopped,
+                timeout,
+                None,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Private helper to start the service unless it is already running, and wait until it is
+    /// running. The paused service is resumed instead, which requires
+    /// [`ServiceAccess::PAUSE_CONTINUE`].
+    pub(crate) fn ensure_running(&self, timeout: Duration) -> crate::Result<()> {
+        let start = Instant::now();
+
+        let mut status = self.query_status()?;
+        if status.current_state == ServiceState::Running {
+            return Ok(());
+        }
+        if status.current_state == ServiceState::PausePending {
+            status = self.poll_status(
+                status,
+                |status| status.current_state != ServiceState::PausePending,
+                timeout,
+                None,
+            )?;
+        }
+        match status.current_state {
+            ServiceState::Paused => {
+                // Starting the paused service fails since it is already running.
+                let status = self.resume()?;
+                if status.current_state != ServiceState::Running {
+                    self.poll_status(
+                        status,
+                        |status| status.current_state == ServiceState::Running,
+                        timeout.saturating_sub(start.elapsed()),
+                        None,
+                    )?;
+                }
+                return Ok(());
+            }
+            ServiceState::Running => return Ok(()),
+            _ => (),
+        }
+        if status.current_state == ServiceState::StopPending {
+            self.poll_status(
+                status,
+                |status| status.current_state == ServiceState::Stopped,
+                timeout,
+                None,
+            )?;
+        }
+
+        match self.start(iter::empty::<&OsStr>()) {
+            // The service may be starting already or have been started by someone else in the
+            // meantime.
+            Ok(()) | Err(Error::ServiceAlreadyRunning) => (),
+            Err(e) => return Err(e),
+        }
+
+        self.wait_for(
+            |status| status.current_state == ServiceState::Running,
+            timeout.saturating_sub(start.elapsed()),
+        )
+        .map(|_| ())
+    }
+
+    /// Private helper to poll the service status until it satisfies the `predicate`.
+    ///
+    /// The `status` is the last known service status, its wait hint is used to determine when
+    /// to poll the service status next time. The `cancel` flag, if any, is checked while
+    /// sleeping between the polls.
+    fn poll_status<F>(
+        &self,
+        mut status: ServiceStatus,
+        predicate: F,
+        timeout: Duration,
+        cancel: Option<&AtomicBool>,
+    ) -> crate::Result<ServiceStatus>
+    where
+        F: Fn(&ServiceStatus) -> bool,
+    {
+        let start = Instant::now();
+        loop {
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+
+            let poll_interval = status
+                .wait_hint
+                .clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL)
+                .min(timeout - elapsed);
+            sleep_unless_cancelled(poll_interval, cancel)?;
+
+            status = self.query_status()?;
+            if predicate(&status) {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Private helper to query the optional configuration parameters of windows services.
+    unsafe fn query_config2<T: Copy>(&self, kind: u32, data: &mut [u8]) -> io::Result<T> {
+        let mut bytes_written: u32 = 0;
+
+        let success = Services::QueryServiceConfig2W(
+            self.service_handle.raw_handle(),
+            kind,
+            data.as_mut_ptr() as _,
+            data.len() as u32,
+            &mut bytes_written,
+        );
+
+        if success == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(*(data.as_ptr() as *const _))
+        }
+    }
+
+    /// Private helper to query the optional configuration parameters of variable length, growing
+    /// the `data` to the size reported by the system until the parameters fit into it.
+    unsafe fn query_variable_config2<T: Copy>(
+        &self,
+        kind: u32,
+        data: &mut Vec<u8>,
+    ) -> io::Result<T> {
+        loop {
+            let mut bytes_needed: u32 = 0;
+
+            let success = Services::QueryServiceConfig2W(
+                self.service_handle.raw_handle(),
+                kind,
+                data.as_mut_ptr() as _,
+                u32::try_from(data.len()).expect("size must fit in u32"),
+                &mut bytes_needed,
+            );
+
+            if success == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) {
+                    return Err(error);
+                }
+                data.resize(usize::try_from(bytes_needed).unwrap(), 0);
+            } else {
+                return Ok(*(data.as_ptr() as *const _));
+            }
+        }
+    }
+
+    /// Private helper to update the optional configuration parameters of windows services.
+    unsafe fn change_config2<T>(&self, kind: u32, data: &mut T) -> io::Result<()> {
+        let success = Services::ChangeServiceConfig2W(
+            self.service_handle.raw_handle(),
+            kind,
+            data as *mut _ as *mut _,
+        );
+
+        if success == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The size of data buffer used by QueryServiceConfig2W for the fixed-size parameters
+const MAX_QUERY_BUFFER_SIZE: usize = 8 * 1024;
+
+/// The bounds of the interval used when polling the service status.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The interval used for checking the cancel flag while waiting for the service.
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Sleep for the given duration, returning [`Error::Cancelled`] as soon as the `cancel` flag, if
+/// any, is set.
+fn sleep_unless_cancelled(duration: Duration, cancel: Option<&AtomicBool>) -> crate::Result<()> {
+    let cancel = match cancel {
+        Some(cancel) => cancel,
+        None => {
+            thread::sleep(duration);
+            return Ok(());
+        }
+    };
+
+    let start = Instant::now();
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(Error::Cancelled);
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= duration {
+            return Ok(());
+        }
+        thread::sleep((duration - elapsed).min(CANCEL_CHECK_INTERVAL));
+    }
+}
+
+/// Convert the duration to milliseconds that fit in a `u32` and are not `INFINITE`.
+fn finite_millis(duration: Duration) -> Option<u32> {
+    u32::try_from(duration.as_millis())
+        .ok()
+        .filter(|&millis| millis != INFINITE)
+}
+
+/// Failure actions for restarting the service after `delay` on every failure.
+fn auto_restart_failure_actions(delay: Duration, reset_period: Duration) -> ServiceFailureActions {
+    ServiceFailureActions {
+        reset_period: ServiceFailureResetPeriod::After(reset_period),
+        reboot_msg: None,
+        command: None,
+        actions: Some(vec![ServiceAction {
+            action_type: ServiceActionType::Restart,
+            delay,
+        }]),
+    }
+}
+
+/// Turn the error returned for the configuration info level that is not supported by the system
+/// into `None`.
+fn optional_config2<T>(result: crate::Result<T>) -> crate::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::Winapi(e)) if e.raw_os_error() == Some(ERROR_INVALID_LEVEL as i32) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn to_wide_slice(
+    s: Option<impl AsRef<OsStr>>,
+) -> ::std::result::Result<Option<Vec<u16>>, ContainsNul<u16>> {
+    if let Some(s) = s {
+        Ok(Some(
+            WideCString::from_os_str(s).map(|s| s.into_vec_with_nul())?,
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseRawError {
+    InvalidInteger(u32),
+    InvalidIntegerSigned(i32),
+    InvalidGuid(String),
+}
+
+impl std::error::Error for ParseRawError {}
+
+impl std::fmt::Display for ParseRawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidInteger(u) => {
+                write!(f, "invalid unsigned integer for the target type: {}", u)
+            }
+            Self::InvalidIntegerSigned(i) => {
+                write!(f, "invalid signed integer for the target type: {}", i)
+            }
+            Self::InvalidGuid(guid) => {
+                write!(f, "invalid GUID value for the target type: {}", guid)
+            }
+        }
+    }
+}
+
+/// An error returned when parsing an unknown name, e.g. of a [`ServiceState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNameError(String);
+
+impl std::error::Error for ParseNameError {}
+
+impl std::fmt::Display for ParseNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown name: {}", self.0)
+    }
+}
+
+/// Private helper to convert the account password into a nul-terminated wide string which is
+/// erased from memory once the call to the Windows API returns.
+fn secret_to_wide(secret: Option<&SecretOsString>) -> crate::Result<Option<SecretOsString>> {
+    secret
+        .map(|secret| {
+            secret
+                .to_nul_terminated()
+                .ok_or(Error::ArgumentHasNulByte("account password"))
+        })
+        .transpose()
+}
+
+/// Check that the buffer holds a valid self-relative security descriptor which fits in it.
+fn is_valid_security_descriptor(
+    descriptor: &[u8],
+    security_information: SecurityInformation,
+) -> bool {
+    let descriptor_len = match u32::try_from(descriptor.len()) {
+        Ok(descriptor_len) => descriptor_len,
+        Err(_) => return false,
+    };
+
+    // Unlike IsValidSecurityDescriptor, this checks that the owner, group and ACLs referenced by
+    // the self-relative descriptor lie within the buffer before reading them.
+    unsafe {
+        RtlValidRelativeSecurityDescriptor(
+            descriptor.as_ptr() as *mut c_void,
+            descriptor_len,
+            security_information.bits(),
+        ) != 0
+    }
+}
+
+fn guid_to_u128(guid: &GUID) -> u128 {
+    (u128::from(guid.data1) << 96)
+        | (u128::from(guid.data2) << 80)
+        | (u128::from(guid.data3) << 64)
+        | u128::from(u64::from_be_bytes(guid.data4))
+}
+
+fn string_from_guid(guid: &GUID) -> String {
+    format!(
+        "{:8X}-{:4X}-{:4X}-{:2X}{:2X}-{:2X}{:2X}{:2X}{:2X}{:2X}{:2X}",
+        guid.data1,
+        guid.data2,
+        guid.data3,
+        guid.data4[0],
+        guid.data4[1],
+        guid.data4[2],
+        guid.data4[3],
+        guid.data4[4],
+        guid.data4[5],
+        guid.data4[6],
+        guid.data4[7]
+    )
+}
+
+pub(crate) fn to_wide(
+    s: Option<impl AsRef<OsStr>>,
+) -> ::std::result::Result<Option<WideCString>, ContainsNul<u16>> {
+    if let Some(s) = s {
+        Ok(Some(WideCString::from_os_str(s)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Escapes a given string, but also checks it does not contain any null bytes
+fn escape_wide(s: impl AsRef<OsStr>) -> ::std::result::Result<WideString, ContainsNul<u16>> {
+    let escaped = shell_escape::escape(Cow::Borrowed(s.as_ref()));
+    let wide = WideCString::from_os_str(escaped)?;
+    Ok(wide.to_ustring())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::{
+        Foundation::{LocalFree, ERROR_ACCESS_DENIED},
+        UI::Shell,
+    };
+
+    /// Split the command line into arguments the way the service process does.
+    fn command_line_to_argv(command_line: &WideCStr) -> Vec<OsString> {
+        let mut argc = 0;
+        let argv = unsafe { Shell::CommandLineToArgvW(command_line.as_ptr(), &mut argc) };
+        assert!(!argv.is_null());
+        let args = (0..argc as usize)
+            .map(|i| unsafe { WideCStr::from_ptr_str(*argv.add(i)) }.to_os_string())
+            .collect();
+        unsafe { LocalFree(argv as _) };
+        args
+    }
+
+    #[test]
+    fn test_service_group_identifier() {
+        let dependency = ServiceDependency::from_system_identifier("+network");
+        assert_eq!(
+            dependency,
+            ServiceDependency::Group(OsString::from("network"))
+        );
+    }
+
+    #[test]
+    fn test_service_name_identifier() {
+        let dependency = ServiceDependency::from_system_identifier("netlogon");
+        assert_eq!(
+            dependency,
+            ServiceDependency::Service(OsString::from("netlogon"))
+        );
+    }
+
+    #[test]
+    fn test_launch_command_round_trip() {
+        let executable_path = Path::new(r"C:\Program Files\My Service\service.exe");
+        let launch_arguments = [
+            OsString::from("--name=\"my service\""),
+            OsString::from(r"C:\path with spaces\"),
+            OsString::from(""),
+            OsString::from("plain"),
+        ];
+        let command_line =
+            launch_command(ServiceType::OWN_PROCESS, executable_path, &launch_arguments).unwrap();
+
+        let mut expected = vec![executable_path.as_os_str().to_os_string()];
+        expected.extend_from_slice(&launch_arguments);
+        assert_eq!(command_line_to_argv(&command_line), expected);
+    }
+
+    #[test]
+    fn test_launch_command_driver_is_not_quoted() {
+        let executable_path = Path::new(r"C:\Program Files\My Driver\driver.sys");
+        let command_line =
+            launch_command(ServiceType::KERNEL_DRIVER, executable_path, &[]).unwrap();
+        assert_eq!(command_line.to_os_string(), executable_path.as_os_str());
+        assert!(matches!(
+            launch_command(
+                ServiceType::KERNEL_DRIVER,
+                executable_path,
+                &[OsString::from("--flag")]
+            ),
+            Err(Error::LaunchArgumentsNotSupported)
+        ));
+    }
+
+    #[test]
+    fn test_joined_dependencies_encoding() {
+        assert_eq!(joined_dependencies(&[]).unwrap(), None);
+
+        let single = [ServiceDependency::Service(OsString::from("netlogon"))];
+        let joined = joined_dependencies(&single).unwrap().unwrap();
+        let expected: Vec<u16> = "netlogon\0\0".encode_utf16().collect();
+        assert_eq!(joined.as_slice(), expected.as_slice());
+
+        let multiple = [
+            ServiceDependency::Service(OsString::from("netlogon")),
+            ServiceDependency::Service(OsString::from("lanmanworkstation")),
+        ];
+        let joined = joined_dependencies(&multiple).unwrap().unwrap();
+        let expected: Vec<u16> = "netlogon\0lanmanworkstation\0\0".encode_utf16().collect();
+        assert_eq!(joined.as_slice(), expected.as_slice());
+
+        let invalid = [ServiceDependency::Service(OsString::from("net\0logon"))];
+        assert!(matches!(
+            joined_dependencies(&invalid),
+            Err(Error::ArgumentHasNulByte("dependency"))
+        ));
+    }
+
+    #[test]
+    fn test_joined_dependencies_prefixes_groups() {
+        let dependencies = [
+            ServiceDependency::Service(OsString::from("netlogon")),
+            ServiceDependency::Group(OsString::from("TDI")),
+        ];
+        let joined = joined_dependencies(&dependencies).unwrap().unwrap();
+        let expected: Vec<u16> = "netlogon\0+TDI\0\0".encode_utf16().collect();
+        assert_eq!(joined.as_slice(), expected.as_slice());
+        assert_eq!(joined_dependencies(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_session_change_from_event() {
+        let mut notification = RemoteDesktop::WTSSESSION_NOTIFICATION {
+            cbSize: mem::size_of::<RemoteDesktop::WTSSESSION_NOTIFICATION>() as u32,
+            dwSessionId: 2,
+        };
+        let control = unsafe {
+            ServiceControl::from_raw(
+                Services::SERVICE_CONTROL_SESSIONCHANGE,
+                WindowsAndMessaging::WTS_SESSION_LOGON,
+                &mut notification as *mut _ as *mut c_void,
+            )
+        }
+        .unwrap();
+
+        match control {
+            ServiceControl::SessionChange(param) => {
+                assert_eq!(param.reason, SessionChangeReason::SessionLogon);
+                assert_eq!(param.notification.session_id, 2);
+            }
+            _ => panic!("unexpected service control: {:?}", control),
+        }
+    }
+
+    #[test]
+    fn test_power_setting_change_from_event() {
+        #[repr(C)]
+        struct RawPowerSetting {
+            power_setting: GUID,
+            data_length: u32,
+            data: i32,
+        }
+
+        let mut setting = RawPowerSetting {
+            power_setting: SystemServices::GUID_ACDC_POWER_SOURCE,
+            data_length: mem::size_of::<i32>() as u32,
+            data: Power::PoDc,
+        };
+        let control = unsafe {
+            ServiceControl::from_raw(
+                Services::SERVICE_CONTROL_POWEREVENT,
+                WindowsAndMessaging::PBT_POWERSETTINGCHANGE,
+                &mut setting as *mut _ as *mut c_void,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(
+            control,
+            ServiceControl::PowerEvent(PowerEventParam::PowerSettingChange(
+                PowerBroadcastSetting::AcdcPowerSource(PowerSource::Dc)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_device_interface_arrival_from_event() {
+        #[repr(C)]
+        struct RawDeviceInterface {
+            size: u32,
+            device_type: u32,
+            reserved: u32,
+            class_guid: GUID,
+            name: [u16; 16],
+        }
+
+        let mut name = [0u16; 16];
+        for (dst, src) in name.iter_mut().zip(r"\\?\USB#VID_1".encode_utf16()) {
+            *dst = src;
+        }
+        let mut broadcast = RawDeviceInterface {
+            size: mem::size_of::<RawDeviceInterface>() as u32,
+            device_type: WindowsAndMessaging::DBT_DEVTYP_DEVICEINTERFACE,
+            reserved: 0,
+            class_guid: unsafe { mem::zeroed() },
+            name,
+        };
+        let control = unsafe {
+            ServiceControl::from_raw(
+                Services::SERVICE_CONTROL_DEVICEEVENT,
+                WindowsAndMessaging::DBT_DEVICEARRIVAL,
+                &mut broadcast as *mut _ as *mut c_void,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(
+            control,
+            ServiceControl::DeviceEvent(DeviceEventParam {
+                event_type: DeviceEventType::Arrival,
+                device_type: DeviceType::DeviceInterface,
+                device_name: Some(OsString::from(r"\\?\USB#VID_1")),
+            })
+        );
+
+        // The name is cut at the size of the structure, even without the nul terminator.
+        let name_offset = mem::size_of::<RawDeviceInterface>() - mem::size_of_val(&name);
+        broadcast.size = (name_offset + 7 * 2) as u32;
+        let param = unsafe {
+            DeviceEventParam::from_event(
+                WindowsAndMessaging::DBT_DEVICEARRIVAL,
+                &mut broadcast as *mut _ as *mut c_void,
+            )
+        }
+        .unwrap();
+        assert_eq!(param.device_name, Some(OsString::from(r"\\?\USB")));
+
+        broadcast.size = (name_offset - 1) as u32;
+        let result = unsafe {
+            DeviceEventParam::from_event(
+                WindowsAndMessaging::DBT_DEVICEARRIVAL,
+                &mut broadcast as *mut _ as *mut c_void,
+            )
+        };
+        assert!(matches!(result, Err(ParseRawError::InvalidInteger(_))));
+    }
+
+    #[test]
+    fn test_device_event_other_device_type() {
+        let mut header = WindowsAndMessaging::DEV_BROADCAST_HDR {
+            dbch_size: mem::size_of::<WindowsAndMessaging::DEV_BROADCAST_HDR>() as u32,
+            dbch_devicetype: 0x100,
+            dbch_reserved: 0,
+        };
+        let control = unsafe {
+            ServiceControl::from_raw(
+                Services::SERVICE_CONTROL_DEVICEEVENT,
+                WindowsAndMessaging::DBT_DEVICEREMOVECOMPLETE,
+                &mut header as *mut _ as *mut c_void,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(
+            control,
+            ServiceControl::DeviceEvent(DeviceEventParam {
+                event_type: DeviceEventType::RemoveComplete,
+                device_type: DeviceType::Other(0x100),
+                device_name: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_device_event_null_event_data() {
+        let result = unsafe {
+            ServiceControl::from_raw(
+                Services::SERVICE_CONTROL_DEVICEEVENT,
+                WindowsAndMessaging::DBT_DEVICEARRIVAL,
+                ptr::null_mut(),
+            )
+        };
+        assert!(matches!(result, Err(ParseRawError::InvalidInteger(0))));
+    }
+
+    #[test]
+    fn test_time_change_null_event_data() {
+        let result = unsafe {
+            ServiceControl::from_raw(Services::SERVICE_CONTROL_TIMECHANGE, 0, ptr::null_mut())
+        };
+        assert!(matches!(result, Err(ParseRawError::InvalidInteger(0))));
+    }
+
+    #[test]
+    fn test_service_info_builder_defaults() {
+        let info = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .build()
+            .unwrap();
+        assert_eq!(info.display_name, OsString::from("my_service"));
+        assert_eq!(info.service_type, ServiceType::OWN_PROCESS);
+        assert_eq!(info.start_type, ServiceStartType::OnDemand);
+        assert_eq!(info.account_name, None);
+    }
+
+    #[test]
+    fn test_service_info_builder_rejects_password_without_account() {
+        let result = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .account_password("secret")
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+    }
+
+    #[test]
+    fn test_service_info_builder_rejects_mixed_process_types() {
+        let result = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .service_type(ServiceType::OWN_PROCESS | ServiceType::SHARE_PROCESS)
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+
+        let result = ServiceInfoBuilder::new("my_driver", r"C:\my_driver.sys")
+            .service_type(ServiceType::KERNEL_DRIVER)
+            .launch_arguments(["--verbose"])
+            .build();
+        assert!(matches!(result, Err(Error::LaunchArgumentsNotSupported)));
+    }
+
+    #[test]
+    fn test_service_info_builder_interactive_local_system() {
+        for account_name in ["LocalSystem", r".\localsystem"] {
+            let result = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+                .service_type(ServiceType::OWN_PROCESS | ServiceType::INTERACTIVE_PROCESS)
+                .account_name(account_name)
+                .build();
+            assert!(result.is_ok(), "{}", account_name);
+        }
+
+        let result = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .service_type(ServiceType::OWN_PROCESS | ServiceType::INTERACTIVE_PROCESS)
+            .account_name(r"NT AUTHORITY\LocalService")
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+    }
+
+    #[test]
+    fn test_service_info_builder_user_service_types() {
+        let info = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .service_type(ServiceType::USER_OWN_PROCESS)
+            .build()
+            .unwrap();
+        assert!(info.service_type.contains(ServiceType::OWN_PROCESS));
+
+        let result = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .service_type(ServiceType::USER_SHARE_PROCESS | ServiceType::INTERACTIVE_PROCESS)
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+
+        let result = ServiceInfoBuilder::new("my_service_3a4b5", r"C:\my_service.exe")
+            .service_type(ServiceType::USER_OWN_PROCESS | ServiceType::USER_SERVICE_INSTANCE)
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+    }
+
+    #[test]
+    fn test_service_info_builder_raw_command_line() {
+        let command_line = r"\SystemRoot\System32\drivers\my driver.sys";
+        let info = ServiceInfoBuilder::new_raw("my_driver", command_line)
+            .service_type(ServiceType::KERNEL_DRIVER)
+            .build()
+            .unwrap();
+        let raw_info = RawServiceInfo::new(&info).unwrap();
+        assert_eq!(raw_info.launch_command.to_os_string(), command_line);
+
+        let result = ServiceInfoBuilder::new_raw("my_service", r"C:\my_service.exe --verbose")
+            .launch_arguments(["--config"])
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+    }
+
+    #[test]
+    fn test_driver_service_rejects_account() {
+        let result = ServiceInfoBuilder::new("my_driver", r"C:\my_driver.sys")
+            .service_type(ServiceType::FILE_SYSTEM_DRIVER)
+            .account_name(r"NT AUTHORITY\LocalService")
+            .build();
+        assert!(matches!(result, Err(Error::InvalidServiceInfo(_))));
+
+        let mut info = ServiceInfoBuilder::new("my_driver", r"C:\my_driver.sys")
+            .service_type(ServiceType::KERNEL_DRIVER)
+            .build()
+            .unwrap();
+        assert!(RawServiceInfo::new(&info).is_ok());
+
+        info.account_password = Some(SecretOsString::from("secret"));
+        assert!(matches!(
+            RawServiceInfo::new(&info),
+            Err(Error::InvalidServiceInfo(_))
+        ));
+    }
+
+    #[test]
+    fn test_raw_service_info_rejects_nul_bytes() {
+        let info = ServiceInfoBuilder::new("my_service\0other", r"C:\my_service.exe")
+            .display_name("My service")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            RawServiceInfo::new(&info),
+            Err(Error::ArgumentHasNulByte("service name"))
+        ));
+
+        let info = ServiceInfoBuilder::new("my_service", r"C:\my_service.exe")
+            .launch_arguments(["--verbose", "--config\0other"])
+            .build()
+            .unwrap();
+        assert!(matches!(
+            RawServiceInfo::new(&info),
+            Err(Error::ArgumentArrayElementHasNulByte("launch argument", 1))
+        ));
+    }
+
+    #[test]
+    fn test_secret_os_string() {
+        let secret = SecretOsString::from("secret");
+        assert_eq!(secret.to_os_string(), OsString::from("secret"));
+        assert_eq!(format!("{:?}", secret), "SecretOsString(\"***\")");
+
+        let nul_terminated = secret.to_nul_terminated().unwrap();
+        assert_eq!(
+            nul_terminated.0,
+            "secret\0".encode_utf16().collect::<Vec<u16>>()
+        );
+        assert!(SecretOsString::from("sec\0ret")
+            .to_nul_terminated()
+            .is_none());
+    }
+
+    #[test]
+    fn test_service_status_controls_accepted_to_raw() {
+        let status = ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: ServiceState::Running,
+            controls_accepted: ServiceControlAccept::STOP | ServiceControlAccept::SESSION_CHANGE,
+            exit_code: ServiceExitCode::Win32(0),
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+            service_flags: ServiceFlags::empty(),
+        };
+        assert_eq!(
+            status.to_raw().dwControlsAccepted,
+            Services::SERVICE_ACCEPT_STOP | Services::SERVICE_ACCEPT_SESSIONCHANGE
+        );
+    }
+
+    #[test]
+    fn test_service_status_accepts() {
+        let status = ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: ServiceState::Running,
+            controls_accepted: ServiceControlAccept::STOP,
+            exit_code: ServiceExitCode::Win32(0),
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+            service_flags: ServiceFlags::empty(),
+        };
+        assert!(status.accepts(&ServiceControl::Stop));
+        assert!(status.accepts(&ServiceControl::Interrogate));
+        assert!(status.accepts(&ServiceControl::UserEvent(
+            UserEventCode::from_raw(128).unwrap()
+        )));
+        assert!(!status.accepts(&ServiceControl::Pause));
+        assert!(!status.accepts(&ServiceControl::Continue));
+        assert!(!status.accepts(&ServiceControl::Shutdown));
+    }
+
+    #[test]
+    fn test_service_exit_code_to_raw() {
+        let mut status = ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: ServiceState::Stopped,
+            controls_accepted: ServiceControlAccept::empty(),
+            exit_code: ServiceExitCode::ServiceSpecific(42),
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+            service_flags: ServiceFlags::empty(),
+        };
+        let raw_status = status.to_raw();
+        assert_eq!(raw_status.dwWin32ExitCode, ERROR_SERVICE_SPECIFIC_ERROR);
+        assert_eq!(raw_status.dwServiceSpecificExitCode, 42);
+        assert_eq!(
+            ServiceExitCode::from(&raw_status),
+            ServiceExitCode::ServiceSpecific(42)
+        );
+
+        status.exit_code = ServiceExitCode::Win32(ERROR_ACCESS_DENIED);
+        let raw_status = status.to_raw();
+        assert_eq!(raw_status.dwWin32ExitCode, ERROR_ACCESS_DENIED);
+        assert_eq!(raw_status.dwServiceSpecificExitCode, 0);
+    }
+
+    #[test]
+    fn test_preshutdown_from_raw() {
+        let control = unsafe {
+            ServiceControl::from_raw(Services::SERVICE_CONTROL_PRESHUTDOWN, 0, ptr::null_mut())
+        }
+        .unwrap();
+        assert_eq!(control, ServiceControl::Preshutdown);
+        assert_eq!(
+            control.raw_service_control_type(),
+            Services::SERVICE_CONTROL_PRESHUTDOWN
+        );
+    }
+
+    #[test]
+    fn test_time_change_from_event() {
+        let mut info = Services::SERVICE_TIMECHANGE_INFO {
+            liNewTime: 133_000_000_000_000_000,
+            liOldTime: 132_000_000_000_000_000,
+        };
+        let control = unsafe {
+            ServiceControl::from_raw(
+                Services::SERVICE_CONTROL_TIMECHANGE,
+                0,
+                &mut info as *mut _ as *mut c_void,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(
+            control,
+            ServiceControl::TimeChange(TimeChangeParam {
+                old_time: 132_000_000_000_000_000,
+                new_time: 133_000_000_000_000_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_trigger_event_from_raw() {
+        let control = unsafe {
+            ServiceControl::from_raw(Services::SERVICE_CONTROL_TRIGGEREVENT, 0, ptr::null_mut())
+        }
+        .unwrap();
+        assert_eq!(control, ServiceControl::TriggerEvent);
+    }
+
+    #[test]
+    fn test_service_status_from_raw_ex_reads_all_fields() {
+        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS_PROCESS>() };
+        raw_status.dwServiceType = Services::SERVICE_WIN32_SHARE_PROCESS;
+        raw_status.dwCurrentState = Services::SERVICE_START_PENDING;
+        raw_status.dwControlsAccepted = Services::SERVICE_ACCEPT_STOP;
+        raw_status.dwWin32ExitCode = ERROR_SERVICE_SPECIFIC_ERROR;
+        raw_status.dwServiceSpecificExitCode = 7;
+        raw_status.dwCheckPoint = 3;
+        raw_status.dwWaitHint = 5000;
+        raw_status.dwProcessId = 1234;
+        raw_status.dwServiceFlags = Services::SERVICE_RUNS_IN_SYSTEM_PROCESS;
+
+        assert_eq!(
+            ServiceStatus::from_raw_ex(raw_status),
+            ServiceStatus {
+                service_type: ServiceType::SHARE_PROCESS,
+                current_state: ServiceState::StartPending,
+                controls_accepted: ServiceControlAccept::STOP,
+                exit_code: ServiceExitCode::ServiceSpecific(7),
+                checkpoint: 3,
+                wait_hint: Duration::from_secs(5),
+                process_id: Some(1234),
+                service_flags: ServiceFlags::RUNS_IN_SYSTEM_PROCESS,
+            }
+        );
+
+        raw_status.dwCurrentState = Services::SERVICE_STOPPED;
+        raw_status.dwControlsAccepted = 0;
+        raw_status.dwProcessId = 0;
+        let status = ServiceStatus::from_raw_ex(raw_status);
+        assert_eq!(status.current_state, ServiceState::Stopped);
+        assert!(status.controls_accepted.is_empty());
+        assert_eq!(status.process_id, None);
+    }
+
+    #[test]
+    fn test_service_error_control_raw_round_trip() {
+        let mappings = [
+            (
+                ServiceErrorControl::Critical,
+                Services::SERVICE_ERROR_CRITICAL,
+            ),
+            (ServiceErrorControl::Ignore, Services::SERVICE_ERROR_IGNORE),
+            (ServiceErrorControl::Normal, Services::SERVICE_ERROR_NORMAL),
+            (ServiceErrorControl::Severe, Services::SERVICE_ERROR_SEVERE),
+        ];
+        for (error_control, raw) in mappings {
+            assert_eq!(error_control.to_raw(), raw);
+            assert_eq!(ServiceErrorControl::from_raw(raw).unwrap(), error_control);
+        }
+        assert!(matches!(
+            ServiceErrorControl::from_raw(42),
+            Err(ParseRawError::InvalidInteger(42))
+        ));
+    }
+
+    #[test]
+    fn test_service_state_raw_round_trip() {
+        for raw in [
+            Services::SERVICE_STOPPED,
+            Services::SERVICE_START_PENDING,
+            Services::SERVICE_STOP_PENDING,
+            Services::SERVICE_RUNNING,
+            Services::SERVICE_CONTINUE_PENDING,
+            Services::SERVICE_PAUSE_PENDING,
+            Services::SERVICE_PAUSED,
+        ] {
+            let state = ServiceState::from_raw(raw);
+            assert!(!matches!(state, ServiceState::Unknown(_)));
+            assert_eq!(state.to_raw(), raw);
+        }
+
+        let unknown = ServiceState::from_raw(42);
+        assert_eq!(unknown, ServiceState::Unknown(42));
+        assert_eq!(unknown.to_raw(), 42);
+        assert_eq!(unknown.to_string(), "unknown(42)");
+
+        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS_PROCESS>() };
+        raw_status.dwCurrentState = 42;
+        assert_eq!(
+            ServiceStatus::from_raw_ex(raw_status).current_state,
+            ServiceState::Unknown(42)
+        );
+    }
+
+    #[test]
+    fn test_service_state_and_control_names() {
+        assert_eq!(ServiceState::StopPending.to_string(), "stop_pending");
+        assert_eq!("running".parse(), Ok(ServiceState::Running));
+        assert!("Running".parse::<ServiceState>().is_err());
 
-        unsafe {
-            self.change_config2(
-                Services::SERVICE_CONFIG_DESCRIPTION,
-                &mut service_description,
-            )
-            .map_err(Error::Winapi)
-        }
+        assert_eq!(ServiceControl::Stop.to_string(), "stop");
+        assert_eq!(
+            ServiceControl::UserEvent(UserEventCode::from_raw(130).unwrap()).to_string(),
+            "user_event"
+        );
+        assert_eq!("preshutdown".parse(), Ok(ServiceControl::Preshutdown));
+        assert!("user_event".parse::<ServiceControl>().is_err());
     }
 
-    /// Set if an auto-start service should be delayed.
-    ///
-    /// If true, the service is started after other auto-start services are started plus a short delay.
-    /// Otherwise, the service is started during system boot. The default is false. This setting is
-    /// ignored unless the service is an auto-start service.
-    ///
-    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
-    pub fn set_delayed_auto_start(&self, delayed: bool) -> crate::Result<()> {
-        let mut delayed = Services::SERVICE_DELAYED_AUTO_START_INFO {
-            fDelayedAutostart: delayed as i32,
+    #[test]
+    fn test_stop_reason_to_raw() {
+        let reason = StopReason {
+            flag: StopReasonFlag::Planned,
+            major: StopReasonMajor::Software,
+            minor: StopReasonMinor::Upgrade,
         };
-        unsafe {
-            self.change_config2(
-                Services::SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
-                &mut delayed,
-            )
-            .map_err(Error::Winapi)
-        }
-    }
+        assert_eq!(reason.to_raw(), 0x4004_0004);
 
-    /// Set the preshutdown timeout value of the service.
-    ///
-    /// When the system prepares to shutdown, the service control manager will send [`ServiceControl::Preshutdown`]
-    /// to any service that accepts [`ServiceControlAccept::PRESHUTDOWN`] and block shutdown until either the
-    /// service is stopped or the preshutdown timeout has elapsed. The default value is 180 seconds on releases
-    /// prior to Windows 10 build 15063, and 10 seconds afterwards. This value is irrelevant unless the service
-    /// handles [`ServiceControl::Preshutdown`].
-    ///
-    /// Panics if the specified timeout is too large to fit as milliseconds in a `u32`.
-    ///
-    /// Required permission: [`ServiceAccess::CHANGE_CONFIG`].
-    pub fn set_preshutdown_timeout(&self, timeout: Duration) -> crate::Result<()> {
-        let mut timeout = Services::SERVICE_PRESHUTDOWN_INFO {
-            dwPreshutdownTimeout: u32::try_from(timeout.as_millis()).expect("Too long timeout"),
+        let reason = StopReason {
+            flag: StopReasonFlag::Custom,
+            major: StopReasonMajor::Custom(0x40),
+            minor: StopReasonMinor::Custom(0x100),
         };
-        unsafe {
-            self.change_config2(Services::SERVICE_CONFIG_PRESHUTDOWN_INFO, &mut timeout)
-                .map_err(Error::Winapi)
+        assert_eq!(reason.to_raw(), 0x2040_0100);
+        assert!(reason.validate().is_ok());
+    }
+
+    #[test]
+    fn test_stop_reason_validate() {
+        let rejected = [
+            (
+                StopReasonFlag::Custom,
+                StopReasonMajor::Custom(0x04),
+                StopReasonMinor::Custom(0x100),
+            ),
+            (
+                StopReasonFlag::Custom,
+                StopReasonMajor::Custom(0x40),
+                StopReasonMinor::Custom(0xff),
+            ),
+            (
+                StopReasonFlag::Custom,
+                StopReasonMajor::Software,
+                StopReasonMinor::Upgrade,
+            ),
+            (
+                StopReasonFlag::Custom,
+                StopReasonMajor::Custom(0x40),
+                StopReasonMinor::Upgrade,
+            ),
+            (
+                StopReasonFlag::Planned,
+                StopReasonMajor::Custom(0x40),
+                StopReasonMinor::Custom(0x100),
+            ),
+        ];
+        for (flag, major, minor) in rejected {
+            let reason = StopReason { flag, major, minor };
+            assert!(
+                matches!(reason.validate(), Err(Error::InvalidServiceInfo(_))),
+                "{:?}",
+                reason
+            );
         }
+
+        let reason = StopReason {
+            flag: StopReasonFlag::Unplanned,
+            major: StopReasonMajor::Hardware,
+            minor: StopReasonMinor::Disk,
+        };
+        assert!(reason.validate().is_ok());
     }
 
-    /// Private helper to send the control commands to the system.
-    fn send_control_command(&self, command: ServiceControl) -> crate::Result<ServiceStatus> {
-        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS>() };
-        let success = unsafe {
-            Services::ControlService(
-                self.service_handle.raw_handle(),
-                command.raw_service_control_type(),
-                &mut raw_status,
-            )
+    #[test]
+    fn test_service_trigger_subtype_guid() {
+        assert_eq!(
+            ServiceTriggerSubtype::from_guid(&trigger::DOMAIN_JOIN_GUID),
+            ServiceTriggerSubtype::DomainJoin
+        );
+
+        let guid = 0x53f5630d_b6bf_11d0_94f2_00a0c91efb8b;
+        let subtype = ServiceTriggerSubtype::from_guid(&GUID::from_u128(guid));
+        assert_eq!(subtype, ServiceTriggerSubtype::Other(guid));
+        assert!(is_equal_guid(&subtype.to_guid(), &GUID::from_u128(guid)));
+    }
+
+    #[test]
+    fn test_service_trigger_round_trip() {
+        let trigger_info = ServiceTriggerInfo {
+            triggers: vec![ServiceTrigger {
+                trigger_type: ServiceTriggerType::Custom,
+                action: ServiceTriggerAction::ServiceStart,
+                trigger_subtype: Some(ServiceTriggerSubtype::Other(1)),
+                data_items: vec![
+                    ServiceTriggerDataItem::String(vec![
+                        OsString::from("USB\\VID_1234"),
+                        OsString::from("USB\\VID_5678"),
+                    ]),
+                    ServiceTriggerDataItem::Level(4),
+                    ServiceTriggerDataItem::KeywordAny(0x8000_0000_0000_0001),
+                    ServiceTriggerDataItem::Binary(vec![1, 2, 3]),
+                    ServiceTriggerDataItem::Other {
+                        data_type: 0x100,
+                        data: vec![4, 5],
+                    },
+                ],
+            }],
         };
 
-        if success == 0 {
-            Err(Error::Winapi(io::Error::last_os_error()))
-        } else {
-            ServiceStatus::from_raw(raw_status).map_err(|e| Error::ParseValue("service status", e))
-        }
+        let mut raw_trigger_info = RawServiceTriggerInfo::new(&trigger_info).unwrap();
+        let raw = Services::SERVICE_TRIGGER_INFO {
+            cTriggers: raw_trigger_info.triggers.len() as u32,
+            pTriggers: raw_trigger_info.triggers.as_mut_ptr(),
+            pReserved: ptr::null_mut(),
+        };
+
+        assert_eq!(
+            unsafe { ServiceTriggerInfo::from_raw(raw) }.unwrap(),
+            trigger_info
+        );
     }
 
-    /// Private helper to query the optional configuration parameters of windows services.
-    unsafe fn query_config2<T: Copy>(&self, kind: u32, data: &mut [u8]) -> io::Result<T> {
-        let mut bytes_written: u32 = 0;
+    #[test]
+    fn test_service_trigger_type_raw() {
+        assert_eq!(
+            ServiceTriggerType::from_raw(Services::SERVICE_TRIGGER_TYPE_AGGREGATE),
+            ServiceTriggerType::Aggregate
+        );
+        assert_eq!(ServiceTriggerType::Aggregate.to_raw(), 30);
+        assert_eq!(
+            ServiceTriggerType::from_raw(0x100),
+            ServiceTriggerType::Other(0x100)
+        );
+        assert_eq!(ServiceTriggerType::Other(0x100).to_raw(), 0x100);
+    }
 
-        let success = Services::QueryServiceConfig2W(
-            self.service_handle.raw_handle(),
-            kind,
-            data.as_mut_ptr() as _,
-            data.len() as u32,
-            &mut bytes_written,
+    #[test]
+    fn test_service_trigger_string_data() {
+        // A single string with one terminator.
+        let mut data: Vec<u8> = "80;TCP\0"
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        let raw = Services::SERVICE_TRIGGER_SPECIFIC_DATA_ITEM {
+            dwDataType: Services::SERVICE_TRIGGER_DATA_TYPE_STRING,
+            cbData: data.len() as u32,
+            pData: data.as_mut_ptr(),
+        };
+        let item = unsafe { ServiceTriggerDataItem::from_raw(raw) }.unwrap();
+        assert_eq!(
+            item,
+            ServiceTriggerDataItem::String(vec![OsString::from("80;TCP")])
         );
 
-        if success == 0 {
-            Err(io::Error::last_os_error())
-        } else {
-            Ok(*(data.as_ptr() as *const _))
-        }
+        let (_, raw_data) = item.to_raw_data().unwrap();
+        let expected: Vec<u8> = "80;TCP\0\0"
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        assert_eq!(raw_data, expected);
+
+        let empty = ServiceTriggerDataItem::String(vec![OsString::new()]);
+        assert!(matches!(
+            empty.to_raw_data(),
+            Err(Error::InvalidServiceInfo(_))
+        ));
     }
 
-    /// Private helper to update the optional configuration parameters of windows services.
-    unsafe fn change_config2<T>(&self, kind: u32, data: &mut T) -> io::Result<()> {
-        let success = Services::ChangeServiceConfig2W(
-            self.service_handle.raw_handle(),
-            kind,
-            data as *mut _ as *mut _,
+    #[test]
+    fn test_failure_reset_period_never() {
+        assert_eq!(
+            ServiceFailureResetPeriod::from_raw(INFINITE),
+            ServiceFailureResetPeriod::Never
         );
+        assert_eq!(ServiceFailureResetPeriod::Never.to_raw().unwrap(), INFINITE);
+    }
 
-        if success == 0 {
-            Err(io::Error::last_os_error())
-        } else {
-            Ok(())
-        }
+    #[test]
+    fn test_failure_reset_period_after() {
+        let period = ServiceFailureResetPeriod::After(Duration::from_secs(86400));
+        assert_eq!(period.to_raw().unwrap(), 86400);
+        assert_eq!(ServiceFailureResetPeriod::from_raw(86400), period);
     }
-}
 
-/// The maximum size of data buffer used by QueryServiceConfigW and QueryServiceConfig2W is 8K
-const MAX_QUERY_BUFFER_SIZE: usize = 8 * 1024;
+    #[test]
+    fn test_failure_reset_period_overflow() {
+        let max = ServiceFailureResetPeriod::After(Duration::from_secs(u64::from(INFINITE - 1)));
+        assert_eq!(max.to_raw().unwrap(), INFINITE - 1);
 
-fn to_wide_slice(
-    s: Option<impl AsRef<OsStr>>,
-) -> ::std::result::Result<Option<Vec<u16>>, ContainsNul<u16>> {
-    if let Some(s) = s {
-        Ok(Some(
-            WideCString::from_os_str(s).map(|s| s.into_vec_with_nul())?,
-        ))
-    } else {
-        Ok(None)
+        for secs in [u64::from(INFINITE), u64::from(u32::MAX) + 1] {
+            let period = ServiceFailureResetPeriod::After(Duration::from_secs(secs));
+            assert!(matches!(period.to_raw(), Err(Error::InvalidServiceInfo(_))));
+        }
     }
-}
 
-#[derive(Debug)]
-pub enum ParseRawError {
-    InvalidInteger(u32),
-    InvalidIntegerSigned(i32),
-    InvalidGuid(String),
-}
+    #[test]
+    fn test_service_action_delay_in_millis() {
+        let action = ServiceAction {
+            action_type: ServiceActionType::Restart,
+            delay: Duration::from_secs(5),
+        };
+        let raw_action = action.to_raw().unwrap();
+        assert_eq!(raw_action.Type, Services::SC_ACTION_RESTART);
+        assert_eq!(raw_action.Delay, 5000);
+        assert_eq!(ServiceAction::from_raw(raw_action).unwrap(), action);
+    }
 
-impl std::error::Error for ParseRawError {}
+    #[test]
+    fn test_service_action_delay_overflow() {
+        let mut action = ServiceAction {
+            action_type: ServiceActionType::Restart,
+            delay: Duration::from_millis(u64::from(INFINITE - 1)),
+        };
+        assert_eq!(action.to_raw().unwrap().Delay, INFINITE - 1);
 
-impl std::fmt::Display for ParseRawError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InvalidInteger(u) => {
-                write!(f, "invalid unsigned integer for the target type: {}", u)
-            }
-            Self::InvalidIntegerSigned(i) => {
-                write!(f, "invalid signed integer for the target type: {}", i)
-            }
-            Self::InvalidGuid(guid) => {
-                write!(f, "invalid GUID value for the target type: {}", guid)
-            }
+        for millis in [u64::from(INFINITE), u64::from(u32::MAX) + 1] {
+            action.delay = Duration::from_millis(millis);
+            assert!(matches!(action.to_raw(), Err(Error::InvalidServiceInfo(_))));
         }
     }
-}
 
-fn string_from_guid(guid: &GUID) -> String {
-    format!(
-        "{:8X}-{:4X}-{:4X}-{:2X}{:2X}-{:2X}{:2X}{:2X}{:2X}{:2X}{:2X}",
-        guid.data1,
-        guid.data2,
-        guid.data3,
-        guid.data4[0],
-        guid.data4[1],
-        guid.data4[2],
-        guid.data4[3],
-        guid.data4[4],
-        guid.data4[5],
-        guid.data4[6],
-        guid.data4[7]
-    )
-}
+    #[test]
+    fn test_service_sid_type_from_raw() {
+        assert_eq!(ServiceSidType::from_raw(0).unwrap(), ServiceSidType::None);
+        assert_eq!(
+            ServiceSidType::from_raw(1).unwrap(),
+            ServiceSidType::Unrestricted
+        );
+        assert_eq!(
+            ServiceSidType::from_raw(3).unwrap(),
+            ServiceSidType::Restricted
+        );
+        assert!(ServiceSidType::from_raw(2).is_err());
+    }
 
-pub(crate) fn to_wide(
-    s: Option<impl AsRef<OsStr>>,
-) -> ::std::result::Result<Option<WideCString>, ContainsNul<u16>> {
-    if let Some(s) = s {
-        Ok(Some(WideCString::from_os_str(s)?))
-    } else {
-        Ok(None)
+    #[test]
+    fn test_service_launch_protection_from_raw() {
+        assert_eq!(
+            ServiceLaunchProtection::from_raw(Services::SERVICE_LAUNCH_PROTECTED_NONE).unwrap(),
+            ServiceLaunchProtection::None
+        );
+        assert_eq!(
+            ServiceLaunchProtection::from_raw(Services::SERVICE_LAUNCH_PROTECTED_WINDOWS).unwrap(),
+            ServiceLaunchProtection::Windows
+        );
+        assert_eq!(
+            ServiceLaunchProtection::from_raw(Services::SERVICE_LAUNCH_PROTECTED_WINDOWS_LIGHT)
+                .unwrap(),
+            ServiceLaunchProtection::WindowsLight
+        );
+        assert_eq!(
+            ServiceLaunchProtection::from_raw(Services::SERVICE_LAUNCH_PROTECTED_ANTIMALWARE_LIGHT)
+                .unwrap(),
+            ServiceLaunchProtection::AntimalwareLight
+        );
+        assert!(ServiceLaunchProtection::from_raw(4).is_err());
     }
-}
 
-/// Escapes a given string, but also checks it does not contain any null bytes
-fn escape_wide(s: impl AsRef<OsStr>) -> ::std::result::Result<WideString, ContainsNul<u16>> {
-    let escaped = shell_escape::escape(Cow::Borrowed(s.as_ref()));
-    let wide = WideCString::from_os_str(escaped)?;
-    Ok(wide.to_ustring())
-}
+    #[test]
+    fn test_sleep_unless_cancelled() {
+        let cancel = AtomicBool::new(false);
+        assert!(sleep_unless_cancelled(Duration::from_millis(1), Some(&cancel)).is_ok());
+        assert!(sleep_unless_cancelled(Duration::from_millis(1), None).is_ok());
+
+        cancel.store(true, Ordering::Relaxed);
+        let start = Instant::now();
+        assert!(matches!(
+            sleep_unless_cancelled(Duration::from_secs(60), Some(&cancel)),
+            Err(Error::Cancelled)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_user_event_code_range() {
+        assert!(UserEventCode::from_raw(127).is_err());
+        assert_eq!(UserEventCode::from_raw(128).unwrap().to_raw(), 128);
+        assert_eq!(UserEventCode::from_raw(255).unwrap().to_raw(), 255);
+        assert!(UserEventCode::from_raw(256).is_err());
+    }
 
     #[test]
-    fn test_service_group_identifier() {
-        let dependency = ServiceDependency::from_system_identifier("+network");
+    fn test_service_entry_from_raw() {
+        let display_name: String = "A very long service display name ".repeat(32);
+        let mut raw_name = WideCString::from_str("my_service")
+            .unwrap()
+            .into_vec_with_nul();
+        let mut raw_display_name = WideCString::from_str(&display_name)
+            .unwrap()
+            .into_vec_with_nul();
+
+        let mut raw_status = unsafe { mem::zeroed::<Services::SERVICE_STATUS_PROCESS>() };
+        raw_status.dwServiceType = Services::SERVICE_WIN32_OWN_PROCESS;
+        raw_status.dwCurrentState = Services::SERVICE_RUNNING;
+        raw_status.dwProcessId = 1234;
+        raw_status.dwServiceFlags = Services::SERVICE_RUNS_IN_SYSTEM_PROCESS;
+
+        let raw_entry = Services::ENUM_SERVICE_STATUS_PROCESSW {
+            lpServiceName: raw_name.as_mut_ptr(),
+            lpDisplayName: raw_display_name.as_mut_ptr(),
+            ServiceStatusProcess: raw_status,
+        };
+        let entry = unsafe { ServiceEntry::from_raw_ex(raw_entry) };
+
+        assert_eq!(entry.name, OsString::from("my_service"));
+        assert_eq!(entry.display_name, OsString::from(display_name));
+        assert_eq!(entry.status.service_type, ServiceType::OWN_PROCESS);
+        assert_eq!(entry.status.current_state, ServiceState::Running);
+        assert_eq!(entry.status.process_id, Some(1234));
         assert_eq!(
-            dependency,
-            ServiceDependency::Group(OsString::from("network"))
+            entry.status.service_flags,
+            ServiceFlags::RUNS_IN_SYSTEM_PROCESS
         );
     }
 
     #[test]
-    fn test_service_name_identifier() {
-        let dependency = ServiceDependency::from_system_identifier("netlogon");
+    fn test_is_valid_security_descriptor_checks_bounds() {
+        // Self-relative descriptor holding only the LocalSystem (S-1-5-18) owner.
+        let mut descriptor = vec![1u8, 0];
+        descriptor.extend_from_slice(&Security::SE_SELF_RELATIVE.to_le_bytes());
+        for offset in [20u32, 0, 0, 0] {
+            descriptor.extend_from_slice(&offset.to_le_bytes());
+        }
+        descriptor.extend_from_slice(&[1, 1, 0, 0, 0, 0, 0, 5]);
+        descriptor.extend_from_slice(&18u32.to_le_bytes());
+
+        assert!(is_valid_security_descriptor(
+            &descriptor,
+            SecurityInformation::OWNER
+        ));
+        assert!(!is_valid_security_descriptor(
+            &descriptor,
+            SecurityInformation::DACL
+        ));
+        assert!(!is_valid_security_descriptor(
+            &descriptor[..24],
+            SecurityInformation::OWNER
+        ));
+        assert!(!is_valid_security_descriptor(
+            &descriptor[..8],
+            SecurityInformation::empty()
+        ));
+    }
+
+    #[test]
+    fn test_finite_millis() {
+        let max = Duration::from_millis(u64::from(INFINITE - 1));
+        assert_eq!(finite_millis(max), Some(INFINITE - 1));
         assert_eq!(
-            dependency,
-            ServiceDependency::Service(OsString::from("netlogon"))
+            finite_millis(Duration::from_millis(u64::from(INFINITE))),
+            None
+        );
+        assert_eq!(
+            finite_millis(Duration::from_millis(u64::from(u32::MAX) + 1)),
+            None
         );
     }
+
+    #[test]
+    fn test_auto_restart_failure_actions() {
+        let failure_actions =
+            auto_restart_failure_actions(Duration::from_secs(5), Duration::from_secs(60));
+        assert_eq!(failure_actions.reset_period.to_raw().unwrap(), 60);
+        assert_eq!(failure_actions.reboot_msg, None);
+        assert_eq!(failure_actions.command, None);
+
+        let raw_actions: Vec<Services::SC_ACTION> = failure_actions
+            .actions
+            .unwrap()
+            .iter()
+            .map(|action| action.to_raw().unwrap())
+            .collect();
+        assert_eq!(raw_actions.len(), 1);
+        assert_eq!(raw_actions[0].Type, Services::SC_ACTION_RESTART);
+        assert_eq!(raw_actions[0].Delay, 5000);
+
+        let too_long = auto_restart_failure_actions(Duration::MAX, Duration::from_secs(60));
+        assert!(too_long.actions.unwrap()[0].to_raw().is_err());
+    }
+
+    #[test]
+    fn test_optional_config2_skips_unsupported_info_level() {
+        let unsupported: crate::Result<bool> = Err(Error::Winapi(io::Error::from_raw_os_error(
+            ERROR_INVALID_LEVEL as i32,
+        )));
+        assert_eq!(optional_config2(unsupported).unwrap(), None);
+        assert_eq!(optional_config2(Ok(true)).unwrap(), Some(true));
+
+        let denied: crate::Result<bool> = Err(Error::Winapi(io::Error::from_raw_os_error(
+            ERROR_ACCESS_DENIED as i32,
+        )));
+        assert!(optional_config2(denied).unwrap_err().is_access_denied());
+    }
 }
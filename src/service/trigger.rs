@@ -0,0 +1,44 @@
+//! Well-known trigger subtypes for use with [`ServiceTrigger`](super::ServiceTrigger).
+//!
+//! The same subtypes are represented by the variants of
+//! [`ServiceTriggerSubtype`](super::ServiceTriggerSubtype), use
+//! [`ServiceTriggerSubtype::from_guid`](super::ServiceTriggerSubtype::from_guid) to convert them.
+
+use windows_sys::core::GUID;
+use windows_sys::Win32::System::Services;
+
+/// The first IP address on the TCP/IP networking stack becomes available.
+pub const NETWORK_MANAGER_FIRST_IP_ADDRESS_ARRIVAL_GUID: GUID =
+    Services::NETWORK_MANAGER_FIRST_IP_ADDRESS_ARRIVAL_GUID;
+
+/// The last IP address on the TCP/IP networking stack becomes unavailable.
+pub const NETWORK_MANAGER_LAST_IP_ADDRESS_REMOVAL_GUID: GUID =
+    Services::NETWORK_MANAGER_LAST_IP_ADDRESS_REMOVAL_GUID;
+
+/// The computer joins a domain.
+pub const DOMAIN_JOIN_GUID: GUID = Services::DOMAIN_JOIN_GUID;
+
+/// The computer leaves a domain.
+pub const DOMAIN_LEAVE_GUID: GUID = Services::DOMAIN_LEAVE_GUID;
+
+/// The firewall port specified in the trigger data is opened.
+pub const FIREWALL_PORT_OPEN_GUID: GUID = Services::FIREWALL_PORT_OPEN_GUID;
+
+/// The firewall port specified in the trigger data is closed.
+pub const FIREWALL_PORT_CLOSE_GUID: GUID = Services::FIREWALL_PORT_CLOSE_GUID;
+
+/// The machine policy is present at startup or changes.
+pub const MACHINE_POLICY_PRESENT_GUID: GUID = Services::MACHINE_POLICY_PRESENT_GUID;
+
+/// The user policy is present at startup or changes.
+pub const USER_POLICY_PRESENT_GUID: GUID = Services::USER_POLICY_PRESENT_GUID;
+
+/// A request arrives on the named pipe specified in the trigger data.
+pub const NAMED_PIPE_EVENT_GUID: GUID = Services::NAMED_PIPE_EVENT_GUID;
+
+/// A request arrives on the RPC interface specified in the trigger data.
+pub const RPC_INTERFACE_EVENT_GUID: GUID = Services::RPC_INTERFACE_EVENT_GUID;
+
+/// A custom system state change specified in the trigger data occurs.
+pub const CUSTOM_SYSTEM_STATE_CHANGE_EVENT_GUID: GUID =
+    Services::CUSTOM_SYSTEM_STATE_CHANGE_EVENT_GUID;
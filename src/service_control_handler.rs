@@ -2,16 +2,52 @@ use std::ffi::OsStr;
 use std::io;
 use std::os::raw::c_void;
 use std::os::windows::io::{AsRawHandle, RawHandle};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
 use widestring::WideCString;
 use windows_sys::Win32::{
     Foundation::{ERROR_CALL_NOT_IMPLEMENTED, NO_ERROR},
     System::Services,
 };
 
-use crate::service::{ServiceControl, ServiceStatus};
+use crate::service::{
+    ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceFlags, ServiceState,
+    ServiceStatus, ServiceType,
+};
 use crate::{Error, Result};
 
 /// A struct that holds a unique token for updating the status of the corresponding service.
+///
+/// The handle is owned by the system and does not have to be closed, so it can be freely copied
+/// and shared between threads, e.g. to report the progress of a lengthy operation from a worker
+/// thread. The system serializes concurrent status updates.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::thread;
+/// use std::time::Duration;
+/// use windows_service::service::{
+///     ServiceControlAccept, ServiceExitCode, ServiceFlags, ServiceState, ServiceStatus,
+///     ServiceType,
+/// };
+/// use windows_service::service_control_handler::ServiceStatusHandle;
+///
+/// fn stop_in_background(status_handle: ServiceStatusHandle) -> thread::JoinHandle<()> {
+///     thread::spawn(move || {
+///         let _ = status_handle.set_service_status(ServiceStatus {
+///             service_type: ServiceType::OWN_PROCESS,
+///             current_state: ServiceState::StopPending,
+///             controls_accepted: ServiceControlAccept::empty(),
+///             exit_code: ServiceExitCode::Win32(0),
+///             checkpoint: 1,
+///             wait_hint: Duration::from_secs(5),
+///             process_id: None,
+///             service_flags: ServiceFlags::empty(),
+///         });
+///     })
+/// }
+/// ```
 #[derive(Debug, Clone, Copy)]
 pub struct ServiceStatusHandle(Services::SERVICE_STATUS_HANDLE);
 
@@ -32,6 +68,53 @@ impl ServiceStatusHandle {
     }
 }
 
+/// A trait for reporting the service status to the system.
+///
+/// The service logic that reports its status through a `StatusReporter` rather than a concrete
+/// [`ServiceStatusHandle`] can be run without registering with the service control manager,
+/// for example in tests. The test doubles are provided by the `test_util` module behind the
+/// `test-util` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use windows_service::service::{
+///     ServiceControlAccept, ServiceExitCode, ServiceFlags, ServiceState, ServiceStatus,
+///     ServiceType,
+/// };
+/// use windows_service::service_control_handler::StatusReporter;
+///
+/// fn report_running(reporter: &impl StatusReporter) -> windows_service::Result<()> {
+///     reporter.set_service_status(ServiceStatus {
+///         service_type: ServiceType::OWN_PROCESS,
+///         current_state: ServiceState::Running,
+///         controls_accepted: ServiceControlAccept::STOP,
+///         exit_code: ServiceExitCode::Win32(0),
+///         checkpoint: 0,
+///         wait_hint: Duration::default(),
+///         process_id: None,
+///         service_flags: ServiceFlags::empty(),
+///     })
+/// }
+/// ```
+pub trait StatusReporter {
+    /// Report the new service status.
+    fn set_service_status(&self, service_status: ServiceStatus) -> Result<()>;
+}
+
+impl StatusReporter for ServiceStatusHandle {
+    fn set_service_status(&self, service_status: ServiceStatus) -> Result<()> {
+        ServiceStatusHandle::set_service_status(self, service_status)
+    }
+}
+
+impl<R: StatusReporter + ?Sized> StatusReporter for &R {
+    fn set_service_status(&self, service_status: ServiceStatus) -> Result<()> {
+        (**self).set_service_status(service_status)
+    }
+}
+
 impl AsRawHandle for ServiceStatusHandle {
     /// Get access to the raw handle to use in other Windows APIs
     fn as_raw_handle(&self) -> RawHandle {
@@ -45,12 +128,137 @@ impl AsRawHandle for ServiceStatusHandle {
 unsafe impl Send for ServiceStatusHandle {}
 unsafe impl Sync for ServiceStatusHandle {}
 
+/// A helper for reporting the progress of a lengthy start, stop, pause or continue operation.
+///
+/// Each call to [`ProgressReporter::tick`] reports the pending state with an incremented
+/// checkpoint, which tells the system that the service is still making progress and prevents it
+/// from assuming that the service has stopped responding.
+///
+/// The progress is reported through a [`ServiceStatusHandle`] by default, or any other
+/// [`StatusReporter`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use windows_service::service::{
+///     ServiceControlAccept, ServiceExitCode, ServiceState, ServiceType,
+/// };
+/// use windows_service::service_control_handler::{ProgressReporter, ServiceStatusHandle};
+///
+/// fn start(status_handle: ServiceStatusHandle) -> windows_service::Result<()> {
+///     let mut progress = ProgressReporter::new(
+///         status_handle,
+///         ServiceType::OWN_PROCESS,
+///         ServiceState::StartPending,
+///     );
+///     for _step in 0..3 {
+///         progress.tick(Duration::from_secs(10))?;
+///         // Perform the next initialization step...
+///     }
+///     progress.finish(
+///         ServiceState::Running,
+///         ServiceControlAccept::STOP,
+///         ServiceExitCode::Win32(0),
+///     )
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ProgressReporter<R: StatusReporter = ServiceStatusHandle> {
+    status_handle: R,
+    service_type: ServiceType,
+    pending_state: ServiceState,
+    checkpoint: u32,
+}
+
+impl<R: StatusReporter> ProgressReporter<R> {
+    /// Create a progress reporter for the operation moving the service through the
+    /// `pending_state`, such as [`ServiceState::StartPending`].
+    pub fn new(status_handle: R, service_type: ServiceType, pending_state: ServiceState) -> Self {
+        ProgressReporter {
+            status_handle,
+            service_type,
+            pending_state,
+            checkpoint: 0,
+        }
+    }
+
+    /// Report the pending state with the next checkpoint.
+    ///
+    /// The `wait_hint` is the estimated time until the next call to `tick` or `finish`.
+    pub fn tick(&mut self, wait_hint: Duration) -> Result<()> {
+        let status = self.next_pending_status(wait_hint);
+        self.status_handle.set_service_status(status)
+    }
+
+    /// Report the final state of the operation, such as [`ServiceState::Running`] or
+    /// [`ServiceState::Stopped`].
+    pub fn finish(
+        self,
+        final_state: ServiceState,
+        controls_accepted: ServiceControlAccept,
+        exit_code: ServiceExitCode,
+    ) -> Result<()> {
+        self.status_handle.set_service_status(ServiceStatus {
+            service_type: self.service_type,
+            current_state: final_state,
+            controls_accepted,
+            exit_code,
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+            service_flags: ServiceFlags::empty(),
+        })
+    }
+
+    fn next_pending_status(&mut self, wait_hint: Duration) -> ServiceStatus {
+        self.checkpoint += 1;
+        ServiceStatus {
+            service_type: self.service_type,
+            current_state: self.pending_state,
+            controls_accepted: ServiceControlAccept::empty(),
+            exit_code: ServiceExitCode::Win32(0),
+            checkpoint: self.checkpoint,
+            wait_hint,
+            process_id: None,
+            service_flags: ServiceFlags::empty(),
+        }
+    }
+}
+
 /// Abstraction over the return value of service control handler.
 /// The meaning of each of variants in this enum depends on the type of received event.
 ///
 /// See the "Return value" section of corresponding MSDN article for more info:
 ///
 /// <https://msdn.microsoft.com/en-us/library/windows/desktop/ms683241(v=vs.85).aspx>
+///
+/// The value returned by the closure passed to [`register`] is handed back to the system as is,
+/// which allows denying the query events, such as [`PowerEventParam::QuerySuspend`] or
+/// [`DeviceEventType::QueryRemove`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use windows_service::service::{PowerEventParam, ServiceControl};
+/// use windows_service::service_control_handler::ServiceControlHandlerResult;
+///
+/// // BROADCAST_QUERY_DENY
+/// const DENY_QUERY: u32 = 0x424D_5144;
+///
+/// fn handle_event(control_event: ServiceControl) -> ServiceControlHandlerResult {
+///     match control_event {
+///         ServiceControl::PowerEvent(PowerEventParam::QuerySuspend) => {
+///             ServiceControlHandlerResult::Other(DENY_QUERY)
+///         }
+///         ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+///         _ => ServiceControlHandlerResult::NotImplemented,
+///     }
+/// }
+/// ```
+///
+/// [`PowerEventParam::QuerySuspend`]: crate::service::PowerEventParam::QuerySuspend
+/// [`DeviceEventType::QueryRemove`]: crate::service::DeviceEventType::QueryRemove
 #[derive(Debug)]
 pub enum ServiceControlHandlerResult {
     /// Either used to acknowledge the call or grant the permission in advanced events.
@@ -132,6 +340,223 @@ where
     }
 }
 
+/// A signal telling the service worker that the system asked the service to stop.
+///
+/// The control handler calls [`StopListener::request_stop`] upon [`ServiceControl::Stop`] or
+/// [`ServiceControl::Shutdown`], which wakes up the threads blocked in [`StopListener::wait`] or
+/// [`StopListener::wait_timeout`]. The clones share the same signal, so one can be moved into the
+/// control handler and another one kept by the worker. Once requested, the stop is never reset.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use windows_service::service::ServiceControl;
+/// use windows_service::service_control_handler::{
+///     self, ServiceControlHandlerResult, StopListener,
+/// };
+///
+/// fn run_service() -> windows_service::Result<()> {
+///     let stop_listener = StopListener::new();
+///     let handler_stop_listener = stop_listener.clone();
+///     let event_handler = move |control_event| -> ServiceControlHandlerResult {
+///         match control_event {
+///             ServiceControl::Stop | ServiceControl::Shutdown => {
+///                 handler_stop_listener.request_stop();
+///                 ServiceControlHandlerResult::NoError
+///             }
+///             ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+///             _ => ServiceControlHandlerResult::NotImplemented,
+///         }
+///     };
+///     let _status_handle = service_control_handler::register("my_service_name", event_handler)?;
+///
+///     // Report the running status, then do the work once a second until asked to stop.
+///     while !stop_listener.wait_timeout(Duration::from_secs(1)) {
+///         // Do the work...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StopListener {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl StopListener {
+    /// Create a listener for which the stop is not requested yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the service to stop, waking up all the waiting threads.
+    pub fn request_stop(&self) {
+        let (stop_requested, condvar) = &*self.inner;
+        *stop_requested
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = true;
+        condvar.notify_all();
+    }
+
+    /// Returns `true` if the stop was requested.
+    pub fn is_stop_requested(&self) -> bool {
+        let (stop_requested, _) = &*self.inner;
+        *stop_requested
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Block the current thread until the stop is requested.
+    pub fn wait(&self) {
+        let (stop_requested, condvar) = &*self.inner;
+        let guard = stop_requested
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let _guard = condvar
+            .wait_while(guard, |stop_requested| !*stop_requested)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+
+    /// Block the current thread until the stop is requested or the timeout elapses.
+    ///
+    /// Returns `true` if the stop was requested.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (stop_requested, condvar) = &*self.inner;
+        let guard = stop_requested
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |stop_requested| !*stop_requested)
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard
+    }
+}
+
+/// The estimated time for the start function passed to [`run_simple_service`] to finish.
+const SIMPLE_SERVICE_START_WAIT_HINT: Duration = Duration::from_secs(30);
+
+/// The estimated time for the service run by [`run_simple_service`] to stop once requested.
+const SIMPLE_SERVICE_STOP_WAIT_HINT: Duration = Duration::from_secs(30);
+
+/// Run a service that can be stopped, taking care of the handler registration and the status
+/// reporting.
+///
+/// The service reports [`ServiceState::StartPending`] while `start` initializes the service, and
+/// [`ServiceState::Running`], accepting [`ServiceControlAccept::STOP`] and
+/// [`ServiceControlAccept::SHUTDOWN`], once it succeeds. The value returned by `start` is passed
+/// to `run` along with the [`StopListener`] signaled when the system asks the service to stop, at
+/// which point the service reports [`ServiceState::StopPending`]. The exit code
+/// returned by `run` is reported along with [`ServiceState::Stopped`]. If `start` fails, the
+/// service reports [`ServiceState::Stopped`] with the returned exit code right away.
+///
+/// This function is meant to be called from the service main function. Use [`register`] and
+/// [`ServiceStatusHandle`] directly for accepting other controls.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// #[macro_use]
+/// extern crate windows_service;
+///
+/// use std::ffi::OsString;
+/// use windows_service::service::{ServiceExitCode, ServiceType};
+/// use windows_service::service_control_handler::{self, StopListener};
+///
+/// define_windows_service!(ffi_service_main, my_service_main);
+///
+/// fn my_service_main(_arguments: Vec<OsString>) {
+///     let _ = service_control_handler::run_simple_service(
+///         "my_service_name",
+///         ServiceType::OWN_PROCESS,
+///         || {
+///             // Initialize the service...
+///             Ok(())
+///         },
+///         |(), stop_listener: StopListener| {
+///             // Do the work until the service is asked to stop.
+///             stop_listener.wait();
+///             ServiceExitCode::NO_ERROR
+///         },
+///     );
+/// }
+///
+/// # fn main() {}
+/// ```
+pub fn run_simple_service<T, S, R>(
+    service_name: impl AsRef<OsStr>,
+    service_type: ServiceType,
+    start: S,
+    run: R,
+) -> Result<()>
+where
+    S: FnOnce() -> std::result::Result<T, ServiceExitCode>,
+    R: FnOnce(T, StopListener) -> ServiceExitCode,
+{
+    let stop_listener = StopListener::new();
+    let handler_stop_listener = stop_listener.clone();
+
+    // The handler is registered before the status handle exists, but the stop controls are only
+    // delivered once the service reports running, by which point the handle is set.
+    let shared_status_handle = Arc::new(Mutex::new(None::<ServiceStatusHandle>));
+    let handler_status_handle = Arc::clone(&shared_status_handle);
+    let event_handler = move |control_event| -> ServiceControlHandlerResult {
+        match control_event {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                let status_handle = handler_status_handle.lock().ok().and_then(|handle| *handle);
+                if let Some(status_handle) = status_handle {
+                    let _ = ProgressReporter::new(
+                        status_handle,
+                        service_type,
+                        ServiceState::StopPending,
+                    )
+                    .tick(SIMPLE_SERVICE_STOP_WAIT_HINT);
+                }
+                handler_stop_listener.request_stop();
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        }
+    };
+
+    let status_handle = register(service_name, event_handler)?;
+    if let Ok(mut handle) = shared_status_handle.lock() {
+        *handle = Some(status_handle);
+    }
+
+    let mut progress =
+        ProgressReporter::new(status_handle, service_type, ServiceState::StartPending);
+    progress.tick(SIMPLE_SERVICE_START_WAIT_HINT)?;
+
+    let state = match start() {
+        Ok(state) => state,
+        Err(exit_code) => {
+            return progress.finish(
+                ServiceState::Stopped,
+                ServiceControlAccept::empty(),
+                exit_code,
+            )
+        }
+    };
+    progress.finish(
+        ServiceState::Running,
+        ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+        ServiceExitCode::NO_ERROR,
+    )?;
+
+    let exit_code = run(state, stop_listener);
+    status_handle.set_service_status(ServiceStatus {
+        service_type,
+        current_state: ServiceState::Stopped,
+        controls_accepted: ServiceControlAccept::empty(),
+        exit_code,
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+        service_flags: ServiceFlags::empty(),
+    })
+}
+
 /// Static service control handler
 #[allow(dead_code)]
 extern "system" fn service_control_handler<F>(
@@ -168,3 +593,73 @@ where
         Err(_) => ServiceControlHandlerResult::NotImplemented.to_raw(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::PowerEventParam;
+    use windows_sys::Win32::UI::WindowsAndMessaging;
+
+    #[test]
+    fn test_service_status_handle_is_shareable() {
+        fn assert_shareable<T: Clone + Copy + Send + Sync>() {}
+        assert_shareable::<ServiceStatusHandle>();
+    }
+
+    #[test]
+    fn test_service_control_handler_returns_handler_result() {
+        const DENY_QUERY: u32 = 0x424D_5144;
+        type Handler = fn(ServiceControl) -> ServiceControlHandlerResult;
+
+        let event_handler: Handler = |control_event| match control_event {
+            ServiceControl::PowerEvent(PowerEventParam::QuerySuspend) => {
+                ServiceControlHandlerResult::Other(DENY_QUERY)
+            }
+            _ => ServiceControlHandlerResult::NoError,
+        };
+        let context = Box::into_raw(Box::new(event_handler));
+
+        let return_code = service_control_handler::<Handler>(
+            Services::SERVICE_CONTROL_POWEREVENT,
+            WindowsAndMessaging::PBT_APMQUERYSUSPEND,
+            std::ptr::null_mut(),
+            context as *mut c_void,
+        );
+        assert_eq!(return_code, DENY_QUERY);
+
+        let _ = unsafe { Box::from_raw(context) };
+    }
+
+    #[test]
+    fn test_stop_listener() {
+        let stop_listener = StopListener::new();
+        assert!(!stop_listener.is_stop_requested());
+        assert!(!stop_listener.wait_timeout(Duration::from_millis(10)));
+
+        let handler_stop_listener = stop_listener.clone();
+        let handle = std::thread::spawn(move || handler_stop_listener.request_stop());
+        stop_listener.wait();
+        handle.join().unwrap();
+
+        assert!(stop_listener.is_stop_requested());
+        assert!(stop_listener.wait_timeout(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_progress_reporter_increments_checkpoint() {
+        let mut progress = ProgressReporter::new(
+            ServiceStatusHandle::from_handle(0),
+            ServiceType::OWN_PROCESS,
+            ServiceState::StartPending,
+        );
+
+        let first = progress.next_pending_status(Duration::from_secs(1));
+        let second = progress.next_pending_status(Duration::from_secs(2));
+
+        assert_eq!(first.current_state, ServiceState::StartPending);
+        assert_eq!(first.checkpoint, 1);
+        assert_eq!(second.checkpoint, 2);
+        assert_eq!(second.wait_hint, Duration::from_secs(2));
+        assert!(second.controls_accepted.is_empty());
+    }
+}
@@ -1,8 +1,14 @@
 use std::ffi::{OsStr, OsString};
-use std::{io, ptr};
+use std::os::windows::ffi::OsStrExt;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{io, ptr, thread};
 
 use widestring::{WideCStr, WideCString};
-use windows_sys::Win32::System::Services;
+use windows_sys::Win32::{
+    Foundation::ERROR_FAILED_SERVICE_CONTROLLER_CONNECT,
+    System::{RemoteDesktop::ProcessIdToSessionId, Services, Threading::GetCurrentProcessId},
+};
 
 use crate::{Error, Result};
 
@@ -18,6 +24,10 @@ use crate::{Error, Result};
 /// responsibility is to create a `ServiceControlHandler`, start processing control events and
 /// report the service status to the system.
 ///
+/// If `$service_main_handler` panics, the panic is caught before it can unwind into the system
+/// and the process is aborted, which the system treats as a service failure and responds to with
+/// the configured failure actions.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -50,19 +60,29 @@ macro_rules! define_windows_service {
                 )
             };
 
-            $service_main_handler(arguments);
+            // Unwinding across the FFI boundary is undefined behavior.
+            let result = ::std::panic::catch_unwind(move || $service_main_handler(arguments));
+            if result.is_err() {
+                ::std::process::abort();
+            }
         }
     };
 }
 
 /// Start service control dispatcher.
 ///
+/// Returns [`Error::InvalidServiceName`] if the `service_name` is empty or contains a slash or a
+/// backslash, or [`Error::ArgumentHasNulByte`] if it contains a nul character, which would
+/// otherwise truncate the name passed to the system.
+///
 /// Once started the service control dispatcher blocks the current thread execution
 /// until the service is stopped.
 ///
 /// Upon successful initialization, system calls the `service_main` on background thread.
 ///
-/// On failure: immediately returns an error, no threads are spawned.
+/// On failure: immediately returns an error, no threads are spawned. The error is
+/// [`Error::NotStartedByServiceController`] when the program was not launched by the system as a
+/// service, such as when it is run from a console.
 ///
 /// # Example
 ///
@@ -91,23 +111,241 @@ pub fn start(
     service_name: impl AsRef<OsStr>,
     service_main: extern "system" fn(u32, *mut *mut u16),
 ) -> Result<()> {
+    validate_service_name(service_name.as_ref())?;
     let service_name = WideCString::from_os_str(service_name)
         .map_err(|_| Error::ArgumentHasNulByte("service name"))?;
-    let service_table: &[Services::SERVICE_TABLE_ENTRYW] = &[
-        Services::SERVICE_TABLE_ENTRYW {
-            lpServiceName: service_name.as_ptr() as _,
-            lpServiceProc: Some(service_main),
-        },
+    start_dispatcher(vec![(service_name, service_main)])
+}
+
+/// Start service control dispatcher for multiple services sharing the current process.
+///
+/// Each service is registered with its own "service_main" callback. The services must be
+/// installed with [`ServiceType::SHARE_PROCESS`](crate::service::ServiceType::SHARE_PROCESS).
+///
+/// Once started the service control dispatcher blocks the current thread execution
+/// until all of the services are stopped.
+///
+/// On failure: immediately returns an error, no threads are spawned. Returns
+/// [`Error::InvalidServiceName`] if the `services` are empty.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// #[macro_use]
+/// extern crate windows_service;
+///
+/// use std::ffi::OsString;
+/// use windows_service::service_dispatcher;
+///
+/// define_windows_service!(ffi_first_service_main, first_service_main);
+/// define_windows_service!(ffi_second_service_main, second_service_main);
+///
+/// fn first_service_main(arguments: Vec<OsString>) {}
+/// fn second_service_main(arguments: Vec<OsString>) {}
+///
+/// fn main() -> windows_service::Result<()> {
+///     service_dispatcher::start_multiple(&[
+///         ("first_service", ffi_first_service_main as _),
+///         ("second_service", ffi_second_service_main as _),
+///     ])?;
+///     Ok(())
+/// }
+/// ```
+pub fn start_multiple<S: AsRef<OsStr>>(
+    services: &[(S, extern "system" fn(u32, *mut *mut u16))],
+) -> Result<()> {
+    // The system would only see the terminating entry of the table.
+    if services.is_empty() {
+        return Err(Error::InvalidServiceName);
+    }
+    let services = services
+        .iter()
+        .enumerate()
+        .map(|(i, (service_name, service_main))| {
+            validate_service_name(service_name.as_ref())?;
+            WideCString::from_os_str(service_name)
+                .map(|service_name| (service_name, *service_main))
+                .map_err(|_| Error::ArgumentArrayElementHasNulByte("service name", i))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    start_dispatcher(services)
+}
+
+/// Start service control dispatcher on a background thread.
+///
+/// Same as [`start`], but instead of blocking the current thread the dispatcher runs on a
+/// dedicated thread, which allows the program to carry on, e.g. in console mode when it was not
+/// launched by the system as a service.
+///
+/// The dispatcher fails right away with [`Error::NotStartedByServiceController`] when the program
+/// was not launched by the system as a service, use [`DispatcherHandle::join_timeout`] to observe
+/// that.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// #[macro_use]
+/// extern crate windows_service;
+///
+/// use std::ffi::OsString;
+/// use std::time::Duration;
+/// use windows_service::service_dispatcher;
+///
+/// define_windows_service!(ffi_service_main, my_service_main);
+///
+/// fn my_service_main(arguments: Vec<OsString>) {}
+///
+/// fn main() -> windows_service::Result<()> {
+///     let dispatcher = service_dispatcher::start_in_background("myservice", ffi_service_main)?;
+///     match dispatcher.join_timeout(Duration::from_millis(500)) {
+///         // The dispatcher failed to start or all services have stopped.
+///         Ok(result) => result,
+///         // The dispatcher is running the service.
+///         Err(dispatcher) => dispatcher.join(),
+///     }
+/// }
+/// ```
+pub fn start_in_background(
+    service_name: impl AsRef<OsStr>,
+    service_main: extern "system" fn(u32, *mut *mut u16),
+) -> Result<DispatcherHandle> {
+    validate_service_name(service_name.as_ref())?;
+    let service_name = WideCString::from_os_str(service_name)
+        .map_err(|_| Error::ArgumentHasNulByte("service name"))?;
+
+    let (result_tx, result_rx) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        let _ = result_tx.send(start_dispatcher(vec![(service_name, service_main)]));
+    });
+
+    Ok(DispatcherHandle {
+        thread,
+        result: result_rx,
+    })
+}
+
+/// A handle to the service control dispatcher running on a background thread.
+///
+/// The instances of [`DispatcherHandle`] can be obtained via [`start_in_background`].
+#[derive(Debug)]
+pub struct DispatcherHandle {
+    thread: thread::JoinHandle<()>,
+    result: mpsc::Receiver<Result<()>>,
+}
+
+impl DispatcherHandle {
+    /// Wait until the dispatcher returns, which happens once all of the services are stopped.
+    pub fn join(self) -> Result<()> {
+        let result = self
+            .result
+            .recv()
+            .expect("dispatcher thread must report the result");
+        let _ = self.thread.join();
+        result
+    }
+
+    /// Wait until the dispatcher returns or the `timeout` elapses.
+    ///
+    /// Returns the result of the dispatcher if it returned in time, otherwise gives the handle
+    /// back.
+    pub fn join_timeout(self, timeout: Duration) -> std::result::Result<Result<()>, Self> {
+        match self.result.recv_timeout(timeout) {
+            Ok(result) => {
+                let _ = self.thread.join();
+                Ok(result)
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(self),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                panic!("dispatcher thread must report the result")
+            }
+        }
+    }
+}
+
+/// Check whether the current process is likely running as a service.
+///
+/// This is a heuristic, which checks whether the process runs in the session 0, where the
+/// services are isolated since Windows Vista. Other processes, such as scheduled tasks, may run
+/// in the session 0 as well. Use it to decide between running the service and running the work
+/// inline from a console for debugging.
+///
+/// The reliable way to tell is to start the dispatcher, which fails with
+/// [`Error::NotStartedByServiceController`] when the process was not launched by the system as
+/// a service, see [`start_in_background`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// #[macro_use]
+/// extern crate windows_service;
+///
+/// use std::ffi::OsString;
+/// use windows_service::service_dispatcher;
+///
+/// define_windows_service!(ffi_service_main, my_service_main);
+///
+/// fn my_service_main(arguments: Vec<OsString>) {}
+///
+/// fn main() -> windows_service::Result<()> {
+///     if service_dispatcher::is_running_as_service() {
+///         service_dispatcher::start("myservice", ffi_service_main)
+///     } else {
+///         // Run the work inline for debugging.
+///         Ok(())
+///     }
+/// }
+/// ```
+pub fn is_running_as_service() -> bool {
+    let mut session_id: u32 = 0;
+    let success = unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session_id) };
+    success != 0 && session_id == 0
+}
+
+/// Private helper to reject the service names that the system would not accept.
+fn validate_service_name(service_name: &OsStr) -> Result<()> {
+    let has_separator = service_name
+        .encode_wide()
+        .any(|c| c == u16::from(b'/') || c == u16::from(b'\\'));
+    if service_name.is_empty() || has_separator {
+        Err(Error::InvalidServiceName)
+    } else {
+        Ok(())
+    }
+}
+
+/// Private helper to start the dispatcher with the table built from the given services.
+fn start_dispatcher(
+    services: Vec<(WideCString, extern "system" fn(u32, *mut *mut u16))>,
+) -> Result<()> {
+    // The table refers to the service names owned by this function, which remain alive until
+    // the blocking call to `StartServiceCtrlDispatcherW` returns.
+    let mut services: Vec<(Vec<u16>, extern "system" fn(u32, *mut *mut u16))> = services
+        .into_iter()
+        .map(|(service_name, service_main)| (service_name.into_vec_with_nul(), service_main))
+        .collect();
+    let service_table: Vec<Services::SERVICE_TABLE_ENTRYW> = services
+        .iter_mut()
+        .map(
+            |(service_name, service_main)| Services::SERVICE_TABLE_ENTRYW {
+                lpServiceName: service_name.as_mut_ptr(),
+                lpServiceProc: Some(*service_main),
+            },
+        )
         // the last item has to be { null, null }
-        Services::SERVICE_TABLE_ENTRYW {
+        .chain(std::iter::once(Services::SERVICE_TABLE_ENTRYW {
             lpServiceName: ptr::null_mut(),
             lpServiceProc: None,
-        },
-    ];
+        }))
+        .collect();
 
     let result = unsafe { Services::StartServiceCtrlDispatcherW(service_table.as_ptr()) };
     if result == 0 {
-        Err(Error::Winapi(io::Error::last_os_error()))
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() == Some(ERROR_FAILED_SERVICE_CONTROLLER_CONNECT as i32) {
+            Err(Error::NotStartedByServiceController)
+        } else {
+            Err(Error::Winapi(error))
+        }
     } else {
         Ok(())
     }
@@ -118,10 +356,93 @@ pub fn start(
 /// This is an implementation detail and *should not* be called directly!
 #[doc(hidden)]
 pub unsafe fn parse_service_arguments(argc: u32, argv: *mut *mut u16) -> Vec<OsString> {
-    (0..argc)
-        .map(|i| {
-            let array_element_ptr: *mut *mut u16 = argv.offset(i as isize);
-            WideCStr::from_ptr_str(*array_element_ptr).to_os_string()
-        })
-        .collect()
+    // The system may pass a null `argv` along with zero arguments.
+    if argc == 0 || argv.is_null() {
+        return Vec::new();
+    }
+    parse_service_arguments_iter(argc, argv).collect()
+}
+
+/// Parse raw arguments received in `service_main` into `OsString`s on demand.
+///
+/// Returns an empty iterator if `argc` is zero or `argv` is null.
+///
+/// # Safety
+///
+/// `argv` must point to `argc` valid nul terminated wide C strings, which remain valid as long as
+/// the iterator is in use.
+pub unsafe fn parse_service_arguments_iter(
+    argc: u32,
+    argv: *mut *mut u16,
+) -> impl Iterator<Item = OsString> {
+    let argc = if argv.is_null() { 0 } else { argc as usize };
+    (0..argc).map(move |i| {
+        let array_element_ptr: *mut *mut u16 = argv.add(i);
+        WideCStr::from_ptr_str(*array_element_ptr).to_os_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_rejects_nul_bytes_in_service_name() {
+        extern "system" fn service_main(_argc: u32, _argv: *mut *mut u16) {}
+
+        assert!(matches!(
+            start("my_service\0other", service_main),
+            Err(Error::ArgumentHasNulByte("service name"))
+        ));
+        assert!(matches!(
+            start_multiple(&[("my_service", service_main), ("my\0service", service_main)]),
+            Err(Error::ArgumentArrayElementHasNulByte("service name", 1))
+        ));
+        assert!(matches!(
+            start_multiple::<&str>(&[]),
+            Err(Error::InvalidServiceName)
+        ));
+    }
+
+    #[test]
+    fn test_parse_service_arguments_null_argv() {
+        assert!(unsafe { parse_service_arguments(0, ptr::null_mut()) }.is_empty());
+        assert!(unsafe { parse_service_arguments(2, ptr::null_mut()) }.is_empty());
+        assert_eq!(
+            unsafe { parse_service_arguments_iter(0, ptr::null_mut()) }.count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_parse_service_arguments() {
+        let mut arguments: Vec<Vec<u16>> = ["my_service", "--verbose"]
+            .iter()
+            .map(|s| WideCString::from_str(s).unwrap().into_vec_with_nul())
+            .collect();
+        let mut argv: Vec<*mut u16> = arguments.iter_mut().map(|s| s.as_mut_ptr()).collect();
+
+        let parsed = unsafe { parse_service_arguments(argv.len() as u32, argv.as_mut_ptr()) };
+        assert_eq!(
+            parsed,
+            vec![OsString::from("my_service"), OsString::from("--verbose")]
+        );
+    }
+
+    #[test]
+    fn test_validate_service_name() {
+        assert!(validate_service_name(OsStr::new("my_service")).is_ok());
+        assert!(matches!(
+            validate_service_name(OsStr::new("")),
+            Err(Error::InvalidServiceName)
+        ));
+        assert!(matches!(
+            validate_service_name(OsStr::new(r"my\service")),
+            Err(Error::InvalidServiceName)
+        ));
+        assert!(matches!(
+            validate_service_name(OsStr::new("my/service")),
+            Err(Error::InvalidServiceName)
+        ));
+    }
 }
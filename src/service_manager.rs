@@ -1,13 +1,23 @@
 use std::ffi::{OsStr, OsString};
+use std::os::raw::c_void;
 use std::os::windows::ffi::OsStringExt;
-use std::{io, ptr};
+use std::time::{Duration, Instant};
+use std::{io, mem, ptr};
 
-use widestring::WideCString;
-use windows_sys::Win32::System::Services;
+use widestring::{WideCStr, WideCString};
+use windows_sys::Win32::{
+    Foundation::{ERROR_INSUFFICIENT_BUFFER, ERROR_MORE_DATA},
+    Security,
+    System::Services,
+};
 
 use crate::sc_handle::ScHandle;
-use crate::service::{to_wide, RawServiceInfo, Service, ServiceAccess, ServiceInfo};
-use crate::{Error, Result};
+use crate::service::{
+    to_wide, ParseRawError, RawServiceInfo, Service, ServiceAccess, ServiceDependency,
+    ServiceEntry, ServiceInfo, ServiceStateFilter, ServiceType,
+};
+use crate::service_notify::{ServiceChangeSubscription, ServiceNotifyMask};
+use crate::{Error, Result, ServiceError};
 
 bitflags::bitflags! {
     /// Flags describing access permissions for [`ServiceManager`].
@@ -22,12 +32,122 @@ bitflags::bitflags! {
         /// Can enumerate services or receive notifications.
         const ENUMERATE_SERVICE = Services::SC_MANAGER_ENUMERATE_SERVICE;
 
+        /// Can lock the services database.
+        const LOCK = Services::SC_MANAGER_LOCK;
+
+        /// Can query the lock status of the services database.
+        const QUERY_LOCK_STATUS = Services::SC_MANAGER_QUERY_LOCK_STATUS;
+
+        /// Can report whether the current boot configuration is acceptable via the
+        /// `NotifyBootConfigStatus` API, which saves it as the last-known good configuration.
+        const MODIFY_BOOT_CONFIG = Services::SC_MANAGER_MODIFY_BOOT_CONFIG;
+
         /// Includes all possible access rights.
         const ALL_ACCESS = Services::SC_MANAGER_ALL_ACCESS;
     }
 }
 
+/// A guard holding the lock of the services database.
+///
+/// The database is unlocked when the guard is dropped.
+#[derive(Debug)]
+pub struct ServiceDatabaseLock(*mut c_void);
+
+impl Drop for ServiceDatabaseLock {
+    fn drop(&mut self) {
+        unsafe { Services::UnlockServiceDatabase(self.0) };
+    }
+}
+
+/// A struct that describes the lock status of the services database.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LockStatus {
+    /// Whether the database is locked.
+    pub is_locked: bool,
+
+    /// The name of the user account that holds the lock.
+    pub lock_owner: Option<OsString>,
+
+    /// The time since the database has been locked.
+    pub lock_duration: Duration,
+}
+
+impl LockStatus {
+    /// Tries to parse a `QUERY_SERVICE_LOCK_STATUSW` into a Rust [`LockStatus`].
+    ///
+    /// # Safety
+    ///
+    /// The `lpLockOwner` must be either null or a valid nul-terminated wide string pointer.
+    unsafe fn from_raw(raw: Services::QUERY_SERVICE_LOCK_STATUSW) -> Self {
+        let lock_owner = if raw.lpLockOwner.is_null() {
+            None
+        } else {
+            Some(WideCStr::from_ptr_str(raw.lpLockOwner).to_os_string())
+        };
+
+        LockStatus {
+            is_locked: raw.fIsLocked != 0,
+            lock_owner,
+            lock_duration: Duration::from_secs(u64::from(raw.dwLockDuration)),
+        }
+    }
+}
+
+/// Enum describing the services database to connect to.
+///
+/// Can be passed as the `database` argument of [`ServiceManager::local_computer`] and
+/// [`ServiceManager::remote_computer`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use windows_service::service_manager::{ServiceDatabase, ServiceManager, ServiceManagerAccess};
+///
+/// # fn main() -> windows_service::Result<()> {
+/// let manager =
+///     ServiceManager::local_computer(Some(ServiceDatabase::Active), ServiceManagerAccess::CONNECT)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServiceDatabase {
+    /// The database of the services that are currently installed.
+    Active,
+
+    /// The database of the failed configuration.
+    ///
+    /// The system only documents the active database as the valid one to connect to, so
+    /// connecting to this database may be rejected.
+    Failed,
+}
+
+impl ServiceDatabase {
+    /// Returns the name of the database, which is `SERVICES_ACTIVE_DATABASE` or
+    /// `SERVICES_FAILED_DATABASE`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceDatabase::Active => "ServicesActive", // SERVICES_ACTIVE_DATABASE
+            ServiceDatabase::Failed => "ServicesFailed", // SERVICES_FAILED_DATABASE
+        }
+    }
+}
+
+impl AsRef<OsStr> for ServiceDatabase {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::new(self.name())
+    }
+}
+
 /// Service manager.
+///
+/// [`ServiceManager`] is `Send` and `Sync`, so it can be shared between threads, e.g. via `Arc`,
+/// to perform concurrent operations on the services database.
+///
+/// Connecting to the service control manager is relatively expensive, so a single
+/// [`ServiceManager`] should be kept for the bulk operations rather than connecting for each of
+/// them. Likewise, the [`Service`] returned by [`ServiceManager::open_service`] keeps its handle
+/// open until it is dropped, so it can be reused for any number of operations allowed by the
+/// requested access.
 pub struct ServiceManager {
     manager_handle: ScHandle,
 }
@@ -70,9 +190,13 @@ impl ServiceManager {
     ///
     /// # Arguments
     ///
-    /// * `database` - The name of database to connect to. Pass `None` to connect to active
-    ///   database.
+    /// * `database` - The name of database to connect to, see [`ServiceDatabase`]. Pass `None`
+    ///   to connect to active database.
     /// * `request_access` - Desired access permissions.
+    ///
+    /// Connecting succeeds for any user, but requesting the access needed to change the services
+    /// database, such as [`ServiceManagerAccess::CREATE_SERVICE`], requires administrator rights.
+    /// See [`crate::is_elevated`].
     pub fn local_computer(
         database: Option<impl AsRef<OsStr>>,
         request_access: ServiceManagerAccess,
@@ -85,8 +209,8 @@ impl ServiceManager {
     /// # Arguments
     ///
     /// * `machine` - The name of remote machine.
-    /// * `database` - The name of database to connect to. Pass `None` to connect to active
-    ///   database.
+    /// * `database` - The name of database to connect to, see [`ServiceDatabase`]. Pass `None`
+    ///   to connect to active database.
     /// * `request_access` - desired access permissions.
     pub fn remote_computer(
         machine: impl AsRef<OsStr>,
@@ -96,6 +220,15 @@ impl ServiceManager {
         ServiceManager::new(Some(machine), database, request_access)
     }
 
+    /// Provides access to the underlying system service control manager handle
+    ///
+    /// Allows calling the Windows APIs not covered by this crate. The handle remains owned by
+    /// [`ServiceManager`] and is closed when it is dropped, so it must not be closed by the caller
+    /// nor used after [`ServiceManager`] is dropped.
+    pub fn raw_handle(&self) -> Security::SC_HANDLE {
+        self.manager_handle.raw_handle()
+    }
+
     /// Create a service.
     ///
     /// # Arguments
@@ -110,7 +243,8 @@ impl ServiceManager {
     /// use std::ffi::OsString;
     /// use std::path::PathBuf;
     /// use windows_service::service::{
-    ///     ServiceAccess, ServiceErrorControl, ServiceInfo, ServiceStartType, ServiceType,
+    ///     ServiceAccess, ServiceBinary, ServiceErrorControl, ServiceInfo, ServiceStartType,
+    ///     ServiceType,
     /// };
     /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
     ///
@@ -124,8 +258,11 @@ impl ServiceManager {
     ///         service_type: ServiceType::OWN_PROCESS,
     ///         start_type: ServiceStartType::OnDemand,
     ///         error_control: ServiceErrorControl::Normal,
-    ///         executable_path: PathBuf::from(r"C:\path\to\my\service.exe"),
-    ///         launch_arguments: vec![],
+    ///         binary: ServiceBinary::Executable {
+    ///             path: PathBuf::from(r"C:\path\to\my\service.exe"),
+    ///             args: vec![],
+    ///         },
+    ///         load_order_group: None,
     ///         dependencies: vec![],
     ///         account_name: None, // run as System
     ///         account_password: None,
@@ -141,6 +278,17 @@ impl ServiceManager {
         service_access: ServiceAccess,
     ) -> Result<Service> {
         let raw_info = RawServiceInfo::new(service_info)?;
+
+        // Tags are only assigned to drivers that belong to a load ordering group.
+        let mut tag_id: u32 = 0;
+        let request_tag_id = raw_info
+            .load_order_group
+            .as_ref()
+            .map_or(false, |group| !group.is_empty())
+            && service_info
+                .service_type
+                .intersects(ServiceType::KERNEL_DRIVER | ServiceType::FILE_SYSTEM_DRIVER);
+
         let service_handle = unsafe {
             Services::CreateServiceW(
                 self.manager_handle.raw_handle(),
@@ -151,8 +299,15 @@ impl ServiceManager {
                 raw_info.start_type,
                 raw_info.error_control,
                 raw_info.launch_command.as_ptr(),
-                ptr::null(),     // load ordering group
-                ptr::null_mut(), // tag id within the load ordering group
+                raw_info
+                    .load_order_group
+                    .as_ref()
+                    .map_or(ptr::null(), |s| s.as_ptr()),
+                if request_tag_id {
+                    &mut tag_id
+                } else {
+                    ptr::null_mut()
+                },
                 raw_info
                     .dependencies
                     .as_ref()
@@ -171,12 +326,65 @@ impl ServiceManager {
         if service_handle == 0 {
             Err(Error::Winapi(io::Error::last_os_error()))
         } else {
-            Ok(Service::new(unsafe { ScHandle::new(service_handle) }))
+            Ok(Service::with_tag_id(
+                unsafe { ScHandle::new(service_handle) },
+                if request_tag_id { Some(tag_id) } else { None },
+            ))
+        }
+    }
+
+    /// Create a service or open it if a service with the same name already exists.
+    ///
+    /// The configuration of an existing service is left untouched, even if it differs from
+    /// `service_info`. Other errors are returned as is, in particular the error reported when
+    /// the display name is already used by a different service.
+    ///
+    /// # Arguments
+    ///
+    /// * `service_info` - The service information that will be saved to the system services
+    ///   registry when the service is created.
+    /// * `service_access` - Desired access permissions for the returned [`Service`] instance.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceAccess, ServiceInfoBuilder};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(
+    ///     None::<&str>,
+    ///     ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE,
+    /// )?;
+    /// let my_service_info =
+    ///     ServiceInfoBuilder::new("my_service", r"C:\path\to\my\service.exe").build()?;
+    /// let my_service =
+    ///     manager.create_or_open_service(&my_service_info, ServiceAccess::QUERY_STATUS)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_or_open_service(
+        &self,
+        service_info: &ServiceInfo,
+        service_access: ServiceAccess,
+    ) -> Result<Service> {
+        match self.create_service(service_info, service_access) {
+            // `ERROR_DUPLICATE_SERVICE_NAME` means that the name is used as a display name by
+            // another service, so it must not be treated as success.
+            Err(e) if e.as_service_error() == Some(ServiceError::AlreadyExists) => {
+                self.open_service(&service_info.name, service_access)
+            }
+            result => result,
         }
     }
 
     /// Open an existing service.
     ///
+    /// Each call opens a new handle, so the returned [`Service`] should be kept and reused
+    /// instead of opening the same service repeatedly, for example when polling its status. To
+    /// query the status of many services at once, prefer [`ServiceManager::enumerate_services`],
+    /// which returns the status of every matching service in a single call without opening them.
+    ///
     /// # Arguments
     ///
     /// * `name` - The service name.
@@ -185,12 +393,18 @@ impl ServiceManager {
     /// # Example
     ///
     /// ```rust,no_run
+    /// use std::thread;
+    /// use std::time::Duration;
     /// use windows_service::service::ServiceAccess;
     /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
     ///
     /// # fn main() -> windows_service::Result<()> {
     /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
     /// let my_service = manager.open_service("my_service", ServiceAccess::QUERY_STATUS)?;
+    /// for _ in 0..10 {
+    ///     println!("{}", my_service.query_status()?.current_state);
+    ///     thread::sleep(Duration::from_secs(1));
+    /// }
     /// # Ok(())
     /// # }
     /// ```
@@ -216,6 +430,121 @@ impl ServiceManager {
         }
     }
 
+    /// Check whether the service with the given name is installed.
+    ///
+    /// The service is opened with [`ServiceAccess::QUERY_STATUS`], which is granted to any
+    /// authenticated user by default. Errors other than the service not being installed, such as
+    /// access denied, are returned as is.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// if !manager.service_exists("my_service")? {
+    ///     println!("The service is not installed");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn service_exists(&self, name: impl AsRef<OsStr>) -> Result<bool> {
+        match self.open_service(name, ServiceAccess::QUERY_STATUS) {
+            Ok(_) => Ok(true),
+            Err(e) if e.as_service_error() == Some(ServiceError::DoesNotExist) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stop the service after stopping all of the services that depend on it.
+    ///
+    /// The active dependent services are stopped one by one, each before the services it
+    /// depends on, waiting until each of them is stopped. The `timeout` applies to the whole
+    /// operation.
+    ///
+    /// Returns [`Error::Timeout`] if any of the services is not stopped before the `timeout`
+    /// elapses.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// manager.stop_with_dependents("my_service", Duration::from_secs(60))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stop_with_dependents(&self, name: impl AsRef<OsStr>, timeout: Duration) -> Result<()> {
+        let start = Instant::now();
+        let service = self.open_service(
+            name,
+            ServiceAccess::ENUMERATE_DEPENDENTS | ServiceAccess::STOP | ServiceAccess::QUERY_STATUS,
+        )?;
+
+        // The dependent services are listed in the reverse of the start order.
+        for dependent in service.enumerate_dependent_services(ServiceStateFilter::Active)? {
+            let dependent = self.open_service(
+                &dependent.name,
+                ServiceAccess::STOP | ServiceAccess::QUERY_STATUS,
+            )?;
+            dependent.ensure_stopped(timeout.saturating_sub(start.elapsed()))?;
+        }
+
+        service.ensure_stopped(timeout.saturating_sub(start.elapsed()))
+    }
+
+    /// Start the service after starting all of the services it depends on.
+    ///
+    /// The dependencies are resolved recursively from the service config and started one by
+    /// one, each after the services it depends on, waiting until each of them is running. The
+    /// paused services are resumed. The `timeout` applies to the whole operation.
+    ///
+    /// Dependencies on load ordering groups are left for the system to resolve when starting the
+    /// services.
+    ///
+    /// Returns [`Error::DependencyCycle`] if the services depend on each other in a cycle, or
+    /// [`Error::Timeout`] if any of the services is not running before the `timeout` elapses.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// manager.start_with_dependencies("my_service", Duration::from_secs(60))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_with_dependencies(
+        &self,
+        name: impl AsRef<OsStr>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let start_order = resolve_start_order(name.as_ref(), &mut |name| {
+            let service = self.open_service(
+                name,
+                ServiceAccess::QUERY_CONFIG
+                    | ServiceAccess::START
+                    | ServiceAccess::PAUSE_CONTINUE
+                    | ServiceAccess::QUERY_STATUS,
+            )?;
+            let dependencies = service.query_config()?.dependencies;
+            Ok((service, dependencies))
+        })?;
+
+        for service in start_order {
+            service.ensure_running(timeout.saturating_sub(start.elapsed()))?;
+        }
+        Ok(())
+    }
+
     /// Return the service name given a service display name.
     ///
     /// # Arguments
@@ -240,26 +569,750 @@ impl ServiceManager {
         let service_display_name = WideCString::from_os_str(display_name)
             .map_err(|_| Error::ArgumentHasNulByte("display name"))?;
 
-        // As per docs, the maximum size of data buffer used by GetServiceKeyNameW is 4k bytes,
-        // which is 2k wchars
-        let mut buffer = [0u16; 2 * 1024];
-        let mut buffer_len = u32::try_from(buffer.len()).expect("size must fit in u32");
-
-        let result = unsafe {
+        Self::query_name(|buffer, buffer_len| unsafe {
             Services::GetServiceKeyNameW(
                 self.manager_handle.raw_handle(),
                 service_display_name.as_ptr(),
-                buffer.as_mut_ptr(),
-                &mut buffer_len,
+                buffer,
+                buffer_len,
+            )
+        })
+    }
+
+    /// Return the service display name given a service name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A service name.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+    /// let my_display_name = manager.display_name_from_service_name("my_service")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn display_name_from_service_name(&self, name: impl AsRef<OsStr>) -> Result<OsString> {
+        let service_name = WideCString::from_os_str(name)
+            .map_err(|_| Error::ArgumentHasNulByte("service name"))?;
+
+        Self::query_name(|buffer, buffer_len| unsafe {
+            Services::GetServiceDisplayNameW(
+                self.manager_handle.raw_handle(),
+                service_name.as_ptr(),
+                buffer,
+                buffer_len,
+            )
+        })
+    }
+
+    /// Private helper to call `GetServiceKeyNameW` or `GetServiceDisplayNameW`, growing the buffer
+    /// to the size reported by the system if the name does not fit into it.
+    fn query_name(mut query: impl FnMut(*mut u16, &mut u32) -> i32) -> Result<OsString> {
+        // As per docs, the maximum size of data buffer used by GetServiceKeyNameW is 4k bytes,
+        // which is 2k wchars. This fits most names in a single call.
+        let mut buffer = vec![0u16; 2 * 1024];
+
+        loop {
+            let mut buffer_len = u32::try_from(buffer.len()).expect("size must fit in u32");
+
+            if query(buffer.as_mut_ptr(), &mut buffer_len) == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER as i32) {
+                    return Err(Error::Winapi(error));
+                }
+                // The returned length excludes the nul terminator.
+                let required_len = usize::try_from(buffer_len).unwrap() + 1;
+                if required_len <= buffer.len() {
+                    // Bail out instead of retrying with the same buffer forever.
+                    return Err(Error::Winapi(error));
+                }
+                buffer.resize(required_len, 0);
+            } else {
+                return Ok(OsString::from_wide(
+                    &buffer[..usize::try_from(buffer_len).unwrap()],
+                ));
+            }
+        }
+    }
+
+    /// Lock the services database.
+    ///
+    /// Starting with Windows Vista the lock is provided only for application compatibility and
+    /// has no effect on the database.
+    ///
+    /// Required permission: [`ServiceManagerAccess::LOCK`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::LOCK)?;
+    /// let lock = manager.lock()?;
+    /// // Change the services configuration...
+    /// drop(lock);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lock(&self) -> Result<ServiceDatabaseLock> {
+        let lock = unsafe { Services::LockServiceDatabase(self.manager_handle.raw_handle()) };
+
+        if lock.is_null() {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(ServiceDatabaseLock(lock))
+        }
+    }
+
+    /// Query the lock status of the services database.
+    ///
+    /// Required permission: [`ServiceManagerAccess::QUERY_LOCK_STATUS`].
+    pub fn query_lock_status(&self) -> Result<LockStatus> {
+        // As per docs, the maximum size of data buffer used by QueryServiceLockStatusW is 4K
+        let mut data = vec![0u8; 4 * 1024];
+        let mut bytes_needed: u32 = 0;
+
+        let success = unsafe {
+            Services::QueryServiceLockStatusW(
+                self.manager_handle.raw_handle(),
+                data.as_mut_ptr() as _,
+                u32::try_from(data.len()).expect("size must fit in u32"),
+                &mut bytes_needed,
             )
         };
 
-        if result == 0 {
+        if success == 0 {
             Err(Error::Winapi(io::Error::last_os_error()))
         } else {
-            Ok(OsString::from_wide(
-                &buffer[..usize::try_from(buffer_len).unwrap()],
-            ))
+            unsafe {
+                let raw_status = ptr::read_unaligned(
+                    data.as_ptr() as *const Services::QUERY_SERVICE_LOCK_STATUSW
+                );
+                Ok(LockStatus::from_raw(raw_status))
+            }
+        }
+    }
+
+    /// Report to the service control manager whether the current boot is acceptable.
+    ///
+    /// Accepting the boot saves the current configuration as the last-known-good control set.
+    /// This is meant to be called by a boot verification program once the system has
+    /// started successfully.
+    ///
+    /// **Warning:** passing `false` tells the system that the boot failed, which may cause it to
+    /// restart and revert to the last-known-good configuration.
+    ///
+    /// Required permission: [`ServiceManagerAccess::MODIFY_BOOT_CONFIG`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager =
+    ///     ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::MODIFY_BOOT_CONFIG)?;
+    /// manager.notify_boot_config_status(true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn notify_boot_config_status(&self, boot_acceptable: bool) -> Result<()> {
+        let success = unsafe { Services::NotifyBootConfigStatus(i32::from(boot_acceptable)) };
+
+        if success == 0 {
+            Err(Error::Winapi(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enumerate services in the services database.
+    ///
+    /// # Arguments
+    ///
+    /// * `service_type` - The types of services to enumerate.
+    /// * `service_state` - The state of services to enumerate.
+    ///
+    /// Required permission: [`ServiceManagerAccess::ENUMERATE_SERVICE`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceStateFilter, ServiceType};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager =
+    ///     ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::ENUMERATE_SERVICE)?;
+    /// let services = manager.enumerate_services(
+    ///     ServiceType::OWN_PROCESS | ServiceType::SHARE_PROCESS,
+    ///     ServiceStateFilter::All,
+    /// )?;
+    /// for service in services {
+    ///     println!("{:?}: {:?}", service.name, service.status.current_state);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// The services on a remote computer are enumerated the same way, given that the caller has
+    /// the permission to connect to its services database. Otherwise an error classified as
+    /// [`ServiceError::AccessDenied`] is returned.
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceStateFilter, ServiceType};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    /// use windows_service::ServiceError;
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::remote_computer(
+    ///     r"\\HOST",
+    ///     None::<&str>,
+    ///     ServiceManagerAccess::CONNECT | ServiceManagerAccess::ENUMERATE_SERVICE,
+    /// )?;
+    /// match manager.enumerate_services(ServiceType::OWN_PROCESS, ServiceStateFilter::Active) {
+    ///     Ok(services) => {
+    ///         for service in services {
+    ///             println!("{:?}: {:?}", service.name, service.status.current_state);
+    ///         }
+    ///     }
+    ///     Err(e) if e.as_service_error() == Some(ServiceError::AccessDenied) => {
+    ///         println!("Not allowed to enumerate the services on HOST")
+    ///     }
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enumerate_services(
+        &self,
+        service_type: ServiceType,
+        service_state: ServiceStateFilter,
+    ) -> Result<Vec<ServiceEntry>> {
+        self.enumerate_services_with_group(service_type, service_state, None)
+    }
+
+    /// Enumerate services that belong to the given load ordering group.
+    ///
+    /// Pass an empty `group_name` to enumerate the services that do not belong to any group.
+    ///
+    /// Required permission: [`ServiceManagerAccess::ENUMERATE_SERVICE`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceStateFilter, ServiceType};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager =
+    ///     ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::ENUMERATE_SERVICE)?;
+    /// let services = manager.enumerate_services_in_group(
+    ///     ServiceType::KERNEL_DRIVER,
+    ///     ServiceStateFilter::All,
+    ///     "NDIS",
+    /// )?;
+    /// for service in services {
+    ///     println!("{:?}: {:?}", service.name, service.status.current_state);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enumerate_services_in_group(
+        &self,
+        service_type: ServiceType,
+        service_state: ServiceStateFilter,
+        group_name: impl AsRef<OsStr>,
+    ) -> Result<Vec<ServiceEntry>> {
+        let group_name = WideCString::from_os_str(group_name)
+            .map_err(|_| Error::ArgumentHasNulByte("group name"))?;
+        self.enumerate_services_with_group(service_type, service_state, Some(&group_name))
+    }
+
+    /// Enumerate services with the display name containing the given pattern, ignoring case.
+    ///
+    /// The system does not support filtering by the display name, so all the services of the
+    /// given type and state are enumerated and filtered afterwards. The returned services are
+    /// sorted by the display name.
+    ///
+    /// Required permission: [`ServiceManagerAccess::ENUMERATE_SERVICE`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceStateFilter, ServiceType};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager =
+    ///     ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::ENUMERATE_SERVICE)?;
+    /// let services = manager.enumerate_services_by_display_name(
+    ///     ServiceType::OWN_PROCESS | ServiceType::SHARE_PROCESS,
+    ///     ServiceStateFilter::All,
+    ///     "windows",
+    /// )?;
+    /// for service in services {
+    ///     println!("{:?}: {:?}", service.display_name, service.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enumerate_services_by_display_name(
+        &self,
+        service_type: ServiceType,
+        service_state: ServiceStateFilter,
+        pattern: impl AsRef<OsStr>,
+    ) -> Result<Vec<ServiceEntry>> {
+        let services = self.enumerate_services(service_type, service_state)?;
+        Ok(filter_by_display_name(services, pattern.as_ref()))
+    }
+
+    /// Enumerate the instances of the user service template with the given name.
+    ///
+    /// The system creates an instance of the service installed with
+    /// [`ServiceType::USER_OWN_PROCESS`] or [`ServiceType::USER_SHARE_PROCESS`] for each user that
+    /// logs on, named after the template followed by an underscore and the identifier of the
+    /// logon session. The instances are opened via [`ServiceManager::open_service`] using the
+    /// returned names to control them.
+    ///
+    /// Required permission: [`ServiceManagerAccess::ENUMERATE_SERVICE`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use windows_service::service::{ServiceAccess, ServiceStateFilter};
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager = ServiceManager::local_computer(
+    ///     None::<&str>,
+    ///     ServiceManagerAccess::CONNECT | ServiceManagerAccess::ENUMERATE_SERVICE,
+    /// )?;
+    /// for instance in
+    ///     manager.enumerate_user_service_instances("WpnUserService", ServiceStateFilter::Active)?
+    /// {
+    ///     let service = manager.open_service(&instance.name, ServiceAccess::STOP)?;
+    ///     service.stop()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enumerate_user_service_instances(
+        &self,
+        template_name: impl AsRef<OsStr>,
+        service_state: ServiceStateFilter,
+    ) -> Result<Vec<ServiceEntry>> {
+        let services = self.enumerate_services(
+            ServiceType::USER_OWN_PROCESS | ServiceType::USER_SHARE_PROCESS,
+            service_state,
+        )?;
+        Ok(services
+            .into_iter()
+            .filter(|service| is_user_service_instance_of(service, template_name.as_ref()))
+            .collect())
+    }
+
+    /// Subscribe for the services being created or deleted.
+    ///
+    /// The `mask` should contain [`ServiceNotifyMask::CREATED`], [`ServiceNotifyMask::DELETED`]
+    /// or both, the other flags are only supported by [`Service::subscribe_status_changes`]. The
+    /// subscription takes ownership of the service control manager, since its handle must remain
+    /// open for as long as the notifications are pending.
+    ///
+    /// Required permission: [`ServiceManagerAccess::ENUMERATE_SERVICE`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    /// use windows_service::service_notify::{ServiceChange, ServiceNotifyMask};
+    ///
+    /// # fn main() -> windows_service::Result<()> {
+    /// let manager =
+    ///     ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::ENUMERATE_SERVICE)?;
+    /// let mut subscription =
+    ///     manager.subscribe_changes(ServiceNotifyMask::CREATED | ServiceNotifyMask::DELETED)?;
+    ///
+    /// while let Some(service_changes) = subscription.next(Duration::from_secs(30))? {
+    ///     for service_change in service_changes {
+    ///         match service_change {
+    ///             ServiceChange::Created(name) => println!("Service created: {:?}", name),
+    ///             ServiceChange::Deleted(name) => println!("Service deleted: {:?}", name),
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Service::subscribe_status_changes`]: crate::service::Service::subscribe_status_changes
+    pub fn subscribe_changes(self, mask: ServiceNotifyMask) -> Result<ServiceChangeSubscription> {
+        ServiceChangeSubscription::new(self, mask)
+    }
+
+    /// Enumerate services, optionally limited to the given load ordering group.
+    fn enumerate_services_with_group(
+        &self,
+        service_type: ServiceType,
+        service_state: ServiceStateFilter,
+        group_name: Option<&WideCStr>,
+    ) -> Result<Vec<ServiceEntry>> {
+        let mut services = Vec::new();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut resume_handle: u32 = 0;
+
+        loop {
+            let mut bytes_needed: u32 = 0;
+            let mut services_returned: u32 = 0;
+
+            let success = unsafe {
+                Services::EnumServicesStatusExW(
+                    self.manager_handle.raw_handle(),
+                    Services::SC_ENUM_PROCESS_INFO,
+                    service_type.bits(),
+                    service_state.to_raw(),
+                    if buffer.is_empty() {
+                        ptr::null_mut()
+                    } else {
+                        buffer.as_mut_ptr()
+                    },
+                    u32::try_from(buffer.len()).expect("size must fit in u32"),
+                    &mut bytes_needed,
+                    &mut services_returned,
+                    &mut resume_handle,
+                    group_name.map_or(ptr::null(), |s| s.as_ptr()),
+                )
+            };
+
+            let error = if success == 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(ERROR_MORE_DATA as i32) {
+                    return Err(Error::Winapi(error));
+                }
+                Some(error)
+            } else {
+                None
+            };
+
+            // Never trust the number of entries beyond what fits in the buffer, e.g. when the
+            // response comes from a remote computer.
+            let entries_size = (services_returned as usize)
+                .checked_mul(mem::size_of::<Services::ENUM_SERVICE_STATUS_PROCESSW>());
+            if entries_size.map_or(true, |size| size > buffer.len()) {
+                return Err(Error::ParseValue(
+                    "number of service entries",
+                    ParseRawError::InvalidInteger(services_returned),
+                ));
+            }
+
+            // The strings referenced by each entry are packed into the same buffer, past the
+            // array of entries.
+            let entries = buffer.as_ptr() as *const Services::ENUM_SERVICE_STATUS_PROCESSW;
+            for i in 0..services_returned as usize {
+                let raw_entry = unsafe { ptr::read_unaligned(entries.add(i)) };
+                services.push(unsafe { ServiceEntry::from_raw_ex(raw_entry) });
+            }
+
+            let error = match error {
+                Some(error) => error,
+                None => return Ok(services),
+            };
+
+            // Grow the buffer to fit the remaining entries and continue from the resume handle.
+            let bytes_needed = usize::try_from(bytes_needed).unwrap();
+            if bytes_needed > buffer.len() {
+                buffer.resize(bytes_needed, 0);
+            } else if services_returned == 0 {
+                // Bail instead of looping forever when the system makes no progress.
+                return Err(Error::Winapi(error));
+            }
+        }
+    }
+}
+
+/// Returns the services in the start order, each after the services it depends on, starting
+/// from the service with the given name.
+///
+/// The `lookup` returns the service and its dependencies given the service name.
+fn resolve_start_order<T, F>(name: &OsStr, lookup: &mut F) -> Result<Vec<T>>
+where
+    F: FnMut(&OsStr) -> Result<(T, Vec<ServiceDependency>)>,
+{
+    let mut start_order = Vec::new();
+    resolve_dependencies(name, lookup, &mut Vec::new(), &mut start_order)?;
+    Ok(start_order
+        .into_iter()
+        .map(|(_, service)| service)
+        .collect())
+}
+
+/// Private helper to append the service to the `start_order` after the services it depends on,
+/// given the chain of services that led to it in `dependents`.
+fn resolve_dependencies<T, F>(
+    name: &OsStr,
+    lookup: &mut F,
+    dependents: &mut Vec<OsString>,
+    start_order: &mut Vec<(OsString, T)>,
+) -> Result<()>
+where
+    F: FnMut(&OsStr) -> Result<(T, Vec<ServiceDependency>)>,
+{
+    // Service names are case insensitive.
+    let key = name.to_ascii_lowercase();
+    if start_order.iter().any(|(resolved, _)| *resolved == key) {
+        return Ok(());
+    }
+    if dependents.contains(&key) {
+        return Err(Error::DependencyCycle(name.to_os_string()));
+    }
+
+    let (service, dependencies) = lookup(name)?;
+
+    dependents.push(key);
+    for dependency in dependencies {
+        if let ServiceDependency::Service(dependency) = dependency {
+            resolve_dependencies(&dependency, lookup, dependents, start_order)?;
+        }
+    }
+    let key = dependents.pop().expect("dependents must not be empty");
+
+    start_order.push((key, service));
+    Ok(())
+}
+
+/// Returns `true` if the service is an instance of the user service template with the given
+/// name, comparing the names ignoring case like the system does.
+///
+/// The instance name is the template name followed by an underscore and the hexadecimal LUID of
+/// the logon session, such as `WpnUserService_3a4b5`.
+fn is_user_service_instance_of(service: &ServiceEntry, template_name: &OsStr) -> bool {
+    let mut prefix = template_name.to_os_string();
+    prefix.push("_");
+    let prefix = prefix.to_string_lossy().to_lowercase();
+    let name = service.name.to_string_lossy().to_lowercase();
+
+    service
+        .status
+        .service_type
+        .contains(ServiceType::USER_SERVICE_INSTANCE)
+        && name.strip_prefix(&prefix).map_or(false, |luid| {
+            !luid.is_empty() && luid.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
+/// Keep the services with the display name containing the pattern, ignoring case, sorted by the
+/// display name.
+fn filter_by_display_name(services: Vec<ServiceEntry>, pattern: &OsStr) -> Vec<ServiceEntry> {
+    let pattern = pattern.to_string_lossy().to_lowercase();
+    let mut services: Vec<(String, ServiceEntry)> = services
+        .into_iter()
+        .map(|service| {
+            (
+                service.display_name.to_string_lossy().to_lowercase(),
+                service,
+            )
+        })
+        .filter(|(display_name, _)| display_name.contains(&pattern))
+        .collect();
+    services.sort_by(|(a, _), (b, _)| a.cmp(b));
+    services.into_iter().map(|(_, service)| service).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{
+        ServiceControlAccept, ServiceExitCode, ServiceFlags, ServiceState, ServiceStatus,
+    };
+    use windows_sys::Win32::Foundation::SetLastError;
+
+    fn service_entry(name: &str, display_name: &str) -> ServiceEntry {
+        service_entry_with_type(name, display_name, ServiceType::OWN_PROCESS)
+    }
+
+    fn service_entry_with_type(
+        name: &str,
+        display_name: &str,
+        service_type: ServiceType,
+    ) -> ServiceEntry {
+        ServiceEntry {
+            name: OsString::from(name),
+            display_name: OsString::from(display_name),
+            status: ServiceStatus {
+                service_type,
+                current_state: ServiceState::Stopped,
+                controls_accepted: ServiceControlAccept::empty(),
+                exit_code: ServiceExitCode::NO_ERROR,
+                checkpoint: 0,
+                wait_hint: Duration::default(),
+                process_id: None,
+                service_flags: ServiceFlags::empty(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_service_manager_and_service_are_shareable() {
+        fn assert_shareable<T: Send + Sync>() {}
+        assert_shareable::<ServiceManager>();
+        assert_shareable::<Service>();
+    }
+
+    #[test]
+    fn test_service_manager_access_rights() {
+        assert_eq!(ServiceManagerAccess::LOCK.bits(), Services::SC_MANAGER_LOCK);
+        assert_eq!(
+            ServiceManagerAccess::QUERY_LOCK_STATUS.bits(),
+            Services::SC_MANAGER_QUERY_LOCK_STATUS
+        );
+        assert_eq!(
+            ServiceManagerAccess::MODIFY_BOOT_CONFIG.bits(),
+            Services::SC_MANAGER_MODIFY_BOOT_CONFIG
+        );
+        assert!(ServiceManagerAccess::ALL_ACCESS.contains(
+            ServiceManagerAccess::CONNECT
+                | ServiceManagerAccess::CREATE_SERVICE
+                | ServiceManagerAccess::ENUMERATE_SERVICE
+                | ServiceManagerAccess::LOCK
+                | ServiceManagerAccess::QUERY_LOCK_STATUS
+                | ServiceManagerAccess::MODIFY_BOOT_CONFIG
+        ));
+    }
+
+    #[test]
+    fn test_filter_by_display_name() {
+        let services = vec![
+            service_entry("wuauserv", "Windows Update"),
+            service_entry("spooler", "Print Spooler"),
+            service_entry("audiosrv", "Windows Audio"),
+        ];
+        let names: Vec<OsString> = filter_by_display_name(services, OsStr::new("WINDOWS"))
+            .into_iter()
+            .map(|service| service.name)
+            .collect();
+        assert_eq!(
+            names,
+            [OsString::from("audiosrv"), OsString::from("wuauserv")]
+        );
+    }
+
+    fn dependency_lookup<'a>(
+        graph: &'a [(&'a str, &'a [&'a str])],
+    ) -> impl FnMut(&OsStr) -> Result<(OsString, Vec<ServiceDependency>)> + 'a {
+        move |name| {
+            let (name, dependencies) = graph
+                .iter()
+                .find(|(service, _)| name.eq_ignore_ascii_case(service))
+                .expect("service must be in the graph");
+            let dependencies = dependencies
+                .iter()
+                .map(ServiceDependency::from_system_identifier)
+                .collect();
+            Ok((OsString::from(name), dependencies))
         }
     }
+
+    #[test]
+    fn test_resolve_start_order() {
+        let graph: &[(&str, &[&str])] = &[
+            ("app", &["db", "Net", "+group"]),
+            ("db", &["net"]),
+            ("net", &[]),
+        ];
+        let start_order =
+            resolve_start_order(OsStr::new("app"), &mut dependency_lookup(graph)).unwrap();
+        assert_eq!(
+            start_order,
+            [
+                OsString::from("net"),
+                OsString::from("db"),
+                OsString::from("app")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_start_order_cycle() {
+        let graph: &[(&str, &[&str])] = &[("app", &["db"]), ("db", &["net"]), ("net", &["APP"])];
+        let result = resolve_start_order(OsStr::new("app"), &mut dependency_lookup(graph));
+        assert!(matches!(result, Err(Error::DependencyCycle(name)) if name == "APP"));
+    }
+
+    #[test]
+    fn test_is_user_service_instance_of() {
+        let instance_type = ServiceType::USER_OWN_PROCESS | ServiceType::USER_SERVICE_INSTANCE;
+        let instance = service_entry_with_type("WpnUserService_3a4b5", "", instance_type);
+        assert!(is_user_service_instance_of(
+            &instance,
+            OsStr::new("wpnuserservice")
+        ));
+        assert!(!is_user_service_instance_of(&instance, OsStr::new("Wpn")));
+
+        let other_instance = service_entry_with_type("Foo_Bar_3a4b5", "", instance_type);
+        assert!(!is_user_service_instance_of(
+            &other_instance,
+            OsStr::new("Foo")
+        ));
+        assert!(is_user_service_instance_of(
+            &other_instance,
+            OsStr::new("Foo_Bar")
+        ));
+
+        let no_luid = service_entry_with_type("Foo_", "", instance_type);
+        assert!(!is_user_service_instance_of(&no_luid, OsStr::new("Foo")));
+
+        let template = service_entry_with_type("WpnUserService", "", ServiceType::USER_OWN_PROCESS);
+        assert!(!is_user_service_instance_of(
+            &template,
+            OsStr::new("WpnUserService")
+        ));
+    }
+
+    #[test]
+    fn test_service_database_name() {
+        let active = unsafe { WideCStr::from_ptr_str(Services::SERVICES_ACTIVE_DATABASEW) };
+        let failed = unsafe { WideCStr::from_ptr_str(Services::SERVICES_FAILED_DATABASEW) };
+        assert_eq!(ServiceDatabase::Active.as_ref(), active.to_os_string());
+        assert_eq!(ServiceDatabase::Failed.as_ref(), failed.to_os_string());
+    }
+
+    #[test]
+    fn test_query_name_grows_buffer() {
+        let name: Vec<u16> = std::iter::repeat(u16::from(b'a')).take(3000).collect();
+        let mut calls = 0;
+
+        let result = ServiceManager::query_name(|buffer, buffer_len| {
+            calls += 1;
+            let capacity = usize::try_from(*buffer_len).unwrap();
+            *buffer_len = u32::try_from(name.len()).unwrap();
+            if capacity <= name.len() {
+                unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+                0
+            } else {
+                unsafe { ptr::copy_nonoverlapping(name.as_ptr(), buffer, name.len()) };
+                1
+            }
+        })
+        .unwrap();
+
+        assert_eq!(calls, 2);
+        assert_eq!(result, OsString::from_wide(&name));
+    }
+
+    #[test]
+    fn test_query_name_fails_if_required_size_does_not_grow() {
+        let result = ServiceManager::query_name(|_buffer, buffer_len| {
+            *buffer_len = 10;
+            unsafe { SetLastError(ERROR_INSUFFICIENT_BUFFER) };
+            0
+        });
+        assert!(matches!(result, Err(Error::Winapi(_))));
+    }
 }
@@ -0,0 +1,394 @@
+use std::ffi::{OsStr, OsString};
+use std::os::raw::c_void;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::time::{Duration, Instant};
+use std::{io, mem, ptr};
+
+use windows_sys::{
+    core::PWSTR,
+    Win32::{
+        Foundation::{LocalFree, NO_ERROR},
+        Security,
+        System::{Services, Threading::SleepEx},
+    },
+};
+
+use crate::double_nul_terminated;
+use crate::service::{Service, ServiceStatus};
+use crate::service_manager::ServiceManager;
+use crate::{Error, Result};
+
+bitflags::bitflags! {
+    /// Flags describing the service status changes to subscribe for.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct ServiceNotifyMask: u32 {
+        /// The service has stopped.
+        const STOPPED = Services::SERVICE_NOTIFY_STOPPED;
+
+        /// The service is starting.
+        const START_PENDING = Services::SERVICE_NOTIFY_START_PENDING;
+
+        /// The service is stopping.
+        const STOP_PENDING = Services::SERVICE_NOTIFY_STOP_PENDING;
+
+        /// The service is running.
+        const RUNNING = Services::SERVICE_NOTIFY_RUNNING;
+
+        /// The service is about to continue.
+        const CONTINUE_PENDING = Services::SERVICE_NOTIFY_CONTINUE_PENDING;
+
+        /// The service is pausing.
+        const PAUSE_PENDING = Services::SERVICE_NOTIFY_PAUSE_PENDING;
+
+        /// The service is paused.
+        const PAUSED = Services::SERVICE_NOTIFY_PAUSED;
+
+        /// The service has been marked for deletion.
+        const DELETE_PENDING = Services::SERVICE_NOTIFY_DELETE_PENDING;
+
+        /// A service has been created.
+        /// Only supported by the subscriptions on the service control manager.
+        const CREATED = Services::SERVICE_NOTIFY_CREATED;
+
+        /// A service has been deleted.
+        /// Only supported by the subscriptions on the service control manager.
+        const DELETED = Services::SERVICE_NOTIFY_DELETED;
+    }
+}
+
+/// Enum describing the change of the installed services, reported by the notifications for
+/// [`ServiceNotifyMask::CREATED`] and [`ServiceNotifyMask::DELETED`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ServiceChange {
+    /// The service with the given name has been created.
+    Created(OsString),
+    /// The service with the given name has been deleted.
+    Deleted(OsString),
+}
+
+impl ServiceChange {
+    /// Parse the service name prefixed by the system with `/` if the service has been created,
+    /// or with `\` if the service has been deleted.
+    ///
+    /// Returns `None` if the name has no known prefix.
+    fn from_prefixed_name(prefixed_name: &OsStr) -> Option<ServiceChange> {
+        const CREATED_PREFIX: u16 = '/' as u16;
+        const DELETED_PREFIX: u16 = '\\' as u16;
+
+        let wide: Vec<u16> = prefixed_name.encode_wide().collect();
+        match wide.split_first() {
+            Some((&CREATED_PREFIX, name)) => {
+                Some(ServiceChange::Created(OsString::from_wide(name)))
+            }
+            Some((&DELETED_PREFIX, name)) => {
+                Some(ServiceChange::Deleted(OsString::from_wide(name)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parse the list of prefixed service names delivered along with the notification.
+///
+/// # Safety
+///
+/// `service_names` must be either null or a valid double nul terminated wide string.
+unsafe fn parse_service_changes(service_names: PWSTR) -> Vec<ServiceChange> {
+    double_nul_terminated::parse_str_ptr(service_names)
+        .iter()
+        .filter_map(|prefixed_name| ServiceChange::from_prefixed_name(prefixed_name))
+        .collect()
+}
+
+/// A struct that describes the service status change delivered by the system.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServiceNotify {
+    /// The status changes that triggered the notification.
+    pub notification_triggered: ServiceNotifyMask,
+
+    /// The service status at the time of notification.
+    pub status: ServiceStatus,
+
+    /// The services created or deleted since the last notification, in the order reported by the
+    /// system. Empty unless the notification was triggered by [`ServiceNotifyMask::CREATED`] or
+    /// [`ServiceNotifyMask::DELETED`].
+    pub service_changes: Vec<ServiceChange>,
+}
+
+impl ServiceNotify {
+    /// Tries to parse a `SERVICE_NOTIFY_2W` delivered by the system into Rust [`ServiceNotify`].
+    ///
+    /// Parses and releases the list of service names allocated by the system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the system failed to retrieve the service status.
+    ///
+    /// # Safety
+    ///
+    /// `pszServiceNames` must be either null or a double nul terminated wide string allocated
+    /// by the system.
+    unsafe fn from_raw(raw: &mut Services::SERVICE_NOTIFY_2W) -> Result<ServiceNotify> {
+        let service_changes = take_service_changes(raw);
+        check_notification_status(raw)?;
+
+        Ok(ServiceNotify {
+            notification_triggered: ServiceNotifyMask::from_bits_truncate(
+                raw.dwNotificationTriggered,
+            ),
+            status: ServiceStatus::from_raw_ex(raw.ServiceStatus),
+            service_changes,
+        })
+    }
+}
+
+/// Parse and release the list of service names allocated by the system.
+///
+/// # Safety
+///
+/// `pszServiceNames` must be either null or a double nul terminated wide string allocated by the
+/// system.
+unsafe fn take_service_changes(raw: &mut Services::SERVICE_NOTIFY_2W) -> Vec<ServiceChange> {
+    let service_changes = parse_service_changes(raw.pszServiceNames);
+    if !raw.pszServiceNames.is_null() {
+        LocalFree(raw.pszServiceNames as _);
+        raw.pszServiceNames = ptr::null_mut();
+    }
+    service_changes
+}
+
+/// Returns the error if the system failed to deliver the notification.
+fn check_notification_status(raw: &Services::SERVICE_NOTIFY_2W) -> Result<()> {
+    if raw.dwNotificationStatus != NO_ERROR {
+        Err(Error::Winapi(io::Error::from_raw_os_error(
+            raw.dwNotificationStatus as i32,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// The state shared with the notification callback.
+struct NotifyContext {
+    buffer: Services::SERVICE_NOTIFY_2W,
+    delivered: bool,
+}
+
+/// The registration for the notifications on a service or service control manager handle.
+///
+/// The owned handle must be closed before the registration is dropped, since closing the handle
+/// cancels the pending notification. The borrowed handle cannot be closed, so the notification
+/// buffer is leaked instead if the notification is still pending.
+struct NotifyRegistration {
+    mask: ServiceNotifyMask,
+    context: *mut NotifyContext,
+    registered: bool,
+    handle_owned: bool,
+}
+
+impl NotifyRegistration {
+    fn new(mask: ServiceNotifyMask, handle_owned: bool) -> Self {
+        let context = Box::into_raw(Box::new(NotifyContext {
+            buffer: unsafe { mem::zeroed::<Services::SERVICE_NOTIFY_2W>() },
+            delivered: false,
+        }));
+        unsafe {
+            (*context).buffer.dwVersion = Services::SERVICE_NOTIFY_STATUS_CHANGE;
+            (*context).buffer.pfnNotifyCallback = Some(notify_callback);
+            (*context).buffer.pContext = context as *mut c_void;
+        }
+
+        NotifyRegistration {
+            mask,
+            context,
+            registered: false,
+            handle_owned,
+        }
+    }
+
+    /// Register for the next notification.
+    fn register(&mut self, handle: Security::SC_HANDLE) -> Result<()> {
+        unsafe { (*self.context).delivered = false };
+
+        let result = unsafe {
+            Services::NotifyServiceStatusChangeW(handle, self.mask.bits(), &(*self.context).buffer)
+        };
+
+        if result != NO_ERROR {
+            Err(Error::Winapi(io::Error::from_raw_os_error(result as i32)))
+        } else {
+            self.registered = true;
+            Ok(())
+        }
+    }
+
+    /// Wait for the next notification, registering for it first unless registered already.
+    ///
+    /// Blocks the current thread in an alertable wait until the system delivers the notification
+    /// or the `timeout` elapses. Returns the delivered notification buffer, or `None` if no
+    /// notification has been delivered in time.
+    fn wait(
+        &mut self,
+        handle: Security::SC_HANDLE,
+        timeout: Duration,
+    ) -> Result<Option<&mut Services::SERVICE_NOTIFY_2W>> {
+        if !self.registered {
+            self.register(handle)?;
+        }
+
+        let start = Instant::now();
+        loop {
+            if unsafe { (*self.context).delivered } {
+                self.registered = false;
+                return Ok(Some(unsafe { &mut (*self.context).buffer }));
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Ok(None);
+            }
+
+            let remaining_ms = u32::try_from((timeout - elapsed).as_millis())
+                .unwrap_or(u32::MAX - 1)
+                .max(1);
+            // The wait is interrupted when any APC is delivered to the thread.
+            unsafe { SleepEx(remaining_ms, 1) };
+        }
+    }
+}
+
+impl Drop for NotifyRegistration {
+    fn drop(&mut self) {
+        if self.registered && !self.handle_owned {
+            // The system may still write to the buffer of the pending notification.
+            return;
+        }
+
+        unsafe {
+            // The handle is closed by now, which cancels the pending notification. Enter a short
+            // alertable wait to run the callbacks queued before that, since they access the
+            // notification buffer.
+            SleepEx(0, 1);
+
+            let mut context = Box::from_raw(self.context);
+            if context.delivered {
+                let _ = take_service_changes(&mut context.buffer);
+            }
+        }
+    }
+}
+
+/// A subscription for the service status changes.
+///
+/// The system delivers the notifications to the thread that subscribed for them, when that thread
+/// enters an alertable wait. Therefore the subscription cannot be sent to other threads.
+///
+/// The instances of [`StatusChangeSubscription`] can be obtained via
+/// [`Service::subscribe_status_changes`].
+pub struct StatusChangeSubscription {
+    // Important: the service is dropped before the registration, see `NotifyRegistration`.
+    service: Service,
+    registration: NotifyRegistration,
+}
+
+impl StatusChangeSubscription {
+    pub(crate) fn new(service: Service, mask: ServiceNotifyMask) -> Result<Self> {
+        let mut registration = NotifyRegistration::new(mask, service.owns_handle());
+        registration.register(service.raw_handle())?;
+        Ok(StatusChangeSubscription {
+            service,
+            registration,
+        })
+    }
+
+    /// Provides access to the subscribed service.
+    pub fn service(&self) -> &Service {
+        &self.service
+    }
+
+    /// Wait for the next service status change.
+    ///
+    /// Blocks the current thread in an alertable wait until the system delivers the notification
+    /// or the `timeout` elapses. Returns `None` if no notification has been delivered in time.
+    pub fn next(&mut self, timeout: Duration) -> Result<Option<ServiceNotify>> {
+        match self.registration.wait(self.service.raw_handle(), timeout)? {
+            Some(raw) => unsafe { ServiceNotify::from_raw(raw) }.map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A subscription for the services being created or deleted.
+///
+/// The notifications are delivered the same way as for [`StatusChangeSubscription`], so the
+/// subscription cannot be sent to other threads either.
+///
+/// The instances of [`ServiceChangeSubscription`] can be obtained via
+/// [`ServiceManager::subscribe_changes`].
+pub struct ServiceChangeSubscription {
+    // Important: the manager is dropped before the registration, see `NotifyRegistration`.
+    manager: ServiceManager,
+    registration: NotifyRegistration,
+}
+
+impl ServiceChangeSubscription {
+    pub(crate) fn new(manager: ServiceManager, mask: ServiceNotifyMask) -> Result<Self> {
+        let mut registration = NotifyRegistration::new(mask, true);
+        registration.register(manager.raw_handle())?;
+        Ok(ServiceChangeSubscription {
+            manager,
+            registration,
+        })
+    }
+
+    /// Provides access to the subscribed service control manager.
+    pub fn manager(&self) -> &ServiceManager {
+        &self.manager
+    }
+
+    /// Wait for the next services being created or deleted.
+    ///
+    /// Blocks the current thread in an alertable wait until the system delivers the notification
+    /// or the `timeout` elapses. Returns the services created or deleted since the previous
+    /// notification, or `None` if no notification has been delivered in time.
+    pub fn next(&mut self, timeout: Duration) -> Result<Option<Vec<ServiceChange>>> {
+        match self.registration.wait(self.manager.raw_handle(), timeout)? {
+            Some(raw) => {
+                let service_changes = unsafe { take_service_changes(raw) };
+                check_notification_status(raw).map(|()| Some(service_changes))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Static notification callback
+extern "system" fn notify_callback(parameter: *const c_void) {
+    unsafe {
+        let buffer = parameter as *const Services::SERVICE_NOTIFY_2W;
+        let context = (*buffer).pContext as *mut NotifyContext;
+        (*context).delivered = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_service_changes() {
+        let mut service_names: Vec<u16> = "/created_service\0\\deleted_service\0unknown\0\0"
+            .encode_utf16()
+            .collect();
+        let service_changes = unsafe { parse_service_changes(service_names.as_mut_ptr()) };
+        assert_eq!(
+            service_changes,
+            [
+                ServiceChange::Created(OsString::from("created_service")),
+                ServiceChange::Deleted(OsString::from("deleted_service")),
+            ]
+        );
+
+        assert!(unsafe { parse_service_changes(ptr::null_mut()) }.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+//! Test doubles for running the service logic without the service control manager.
+//!
+//! This module requires the `test-util` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use windows_service::service::{
+//!     ServiceControlAccept, ServiceExitCode, ServiceState, ServiceType,
+//! };
+//! use windows_service::service_control_handler::{ProgressReporter, StatusReporter};
+//! use windows_service::test_util::RecordingStatusReporter;
+//!
+//! fn start(reporter: impl StatusReporter) -> windows_service::Result<()> {
+//!     let mut progress =
+//!         ProgressReporter::new(reporter, ServiceType::OWN_PROCESS, ServiceState::StartPending);
+//!     progress.tick(Duration::from_secs(10))?;
+//!     progress.finish(
+//!         ServiceState::Running,
+//!         ServiceControlAccept::STOP,
+//!         ServiceExitCode::Win32(0),
+//!     )
+//! }
+//!
+//! let reporter = RecordingStatusReporter::new();
+//! start(&reporter).unwrap();
+//! assert_eq!(
+//!     reporter.states(),
+//!     [ServiceState::StartPending, ServiceState::Running]
+//! );
+//! ```
+
+use std::sync::{Mutex, PoisonError};
+
+use crate::service::{ServiceState, ServiceStatus};
+use crate::service_control_handler::StatusReporter;
+use crate::Result;
+
+/// A [`StatusReporter`] that discards all reported statuses.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullStatusReporter;
+
+impl StatusReporter for NullStatusReporter {
+    fn set_service_status(&self, _service_status: ServiceStatus) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A [`StatusReporter`] that records all reported statuses in order.
+///
+/// The recorder can be shared between threads, e.g. with the service control handler and the
+/// service worker thread.
+#[derive(Debug, Default)]
+pub struct RecordingStatusReporter {
+    statuses: Mutex<Vec<ServiceStatus>>,
+}
+
+impl RecordingStatusReporter {
+    /// Create a recorder without any statuses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all the statuses reported so far.
+    pub fn statuses(&self) -> Vec<ServiceStatus> {
+        self.lock().clone()
+    }
+
+    /// Returns the states of all the statuses reported so far.
+    pub fn states(&self) -> Vec<ServiceState> {
+        self.lock()
+            .iter()
+            .map(|status| status.current_state)
+            .collect()
+    }
+
+    /// Returns the last reported status.
+    pub fn last_status(&self) -> Option<ServiceStatus> {
+        self.lock().last().cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<ServiceStatus>> {
+        self.statuses.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl StatusReporter for RecordingStatusReporter {
+    fn set_service_status(&self, service_status: ServiceStatus) -> Result<()> {
+        self.lock().push(service_status);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{ServiceControlAccept, ServiceExitCode, ServiceType};
+    use crate::service_control_handler::ProgressReporter;
+    use std::time::Duration;
+
+    #[test]
+    fn test_recording_status_reporter() {
+        let reporter = RecordingStatusReporter::new();
+        let mut progress = ProgressReporter::new(
+            &reporter,
+            ServiceType::OWN_PROCESS,
+            ServiceState::StopPending,
+        );
+        progress.tick(Duration::from_secs(1)).unwrap();
+        progress.tick(Duration::from_secs(1)).unwrap();
+        progress
+            .finish(
+                ServiceState::Stopped,
+                ServiceControlAccept::empty(),
+                ServiceExitCode::Win32(0),
+            )
+            .unwrap();
+
+        assert_eq!(
+            reporter.states(),
+            [
+                ServiceState::StopPending,
+                ServiceState::StopPending,
+                ServiceState::Stopped
+            ]
+        );
+        let checkpoints: Vec<u32> = reporter
+            .statuses()
+            .iter()
+            .map(|status| status.checkpoint)
+            .collect();
+        assert_eq!(checkpoints, [1, 2, 0]);
+        assert_eq!(reporter.last_status().unwrap().checkpoint, 0);
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(windows)]
+
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use windows_service::{
+    service::{ServiceAccess, ServiceInfoBuilder},
+    service_manager::{ServiceManager, ServiceManagerAccess},
+};
+use windows_sys::Win32::Foundation::ERROR_SERVICE_DOES_NOT_EXIST;
+
+const SERVICE_NAME: &str = "windows_service_delete_test";
+
+// Requires administrator privileges, run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_delete_service() {
+    let manager_access = ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE;
+    let service_manager = ServiceManager::local_computer(None::<&str>, manager_access).unwrap();
+
+    let service_info = ServiceInfoBuilder::new(SERVICE_NAME, std::env::current_exe().unwrap())
+        .build()
+        .unwrap();
+    let service = service_manager
+        .create_service(&service_info, ServiceAccess::DELETE)
+        .unwrap();
+
+    service.delete().unwrap();
+    // The service is not removed from the database until all open handles to it are closed.
+    drop(service);
+
+    let start = Instant::now();
+    loop {
+        match service_manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS) {
+            Err(windows_service::Error::Winapi(e))
+                if e.raw_os_error() == Some(ERROR_SERVICE_DOES_NOT_EXIST as i32) =>
+            {
+                break;
+            }
+            _ if start.elapsed() < Duration::from_secs(5) => sleep(Duration::from_millis(100)),
+            result => panic!("service is not deleted: {:?}", result.map(|_| ())),
+        }
+    }
+}